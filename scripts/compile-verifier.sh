@@ -0,0 +1,19 @@
+#!/usr/bin/env sh
+# Regenerate the Solidity verifier of the bytecode test and compile it with the pinned solc:
+#   tests/data/PolymathVerifier.sol - the contract for the key of the test fixture
+#   tests/data/PolymathVerifier.bin - its creation bytecode, deployed in revm by the test
+set -eu
+
+SOLC_VERSION=0.8.26
+SOLC=${SOLC:-solc}
+
+cd "$(dirname "$0")/.."
+
+if ! "$SOLC" --version | grep -q "Version: $SOLC_VERSION+"; then
+    echo "solc $SOLC_VERSION is required, set SOLC to its path" >&2
+    exit 1
+fi
+
+REGENERATE_VERIFIER=1 cargo test --features solidity --test solidity checked_in_contract_is_up_to_date
+"$SOLC" --optimize --optimize-runs 200 --evm-version cancun --bin \
+    -o tests/data --overwrite tests/data/PolymathVerifier.sol
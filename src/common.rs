@@ -1,9 +1,19 @@
 use ark_ec::pairing::Pairing;
-use ark_ff::{Field, PrimeField};
+use ark_ff::{batch_inversion, FftField, Field, PrimeField};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_relations::r1cs::Matrix;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-
-use crate::{to_bytes, Polymath, PolymathError, Transcript, VerifyingKey};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::{
+    io::{Read, Write},
+    iter,
+    vec::Vec,
+};
+
+use crate::{
+    to_bytes, FiatShamirVersion, Polymath, PolymathError, SapRow, Transcript, VerifyingKey,
+};
 
 pub const B_POLYMATH: &[u8; 8] = b"polymath";
 
@@ -13,25 +23,47 @@ pub const MINUS_ALPHA: u64 = 3;
 /// `𝛾` is negative, we use it as an exponent of `y`: `y^𝛾 = (1/y)^(-𝛾)`
 pub const MINUS_GAMMA: u64 = 5;
 
-impl<F: PrimeField, E, T> Polymath<E, T>
+/// Degree bound of the randomizer polynomial `r_a(X)` used to mask `A(X)`.
+pub const BND_A: usize = 1;
+
+impl<F: PrimeField, E, T, V> Polymath<E, T, V>
 where
     E: Pairing<ScalarField = F>,
     T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
 {
+    /// Start a transcript for a proof against `vk`, absorbing the protocol version
+    /// and the key digest if `V` requires it.
+    pub(crate) fn new_transcript(vk: &VerifyingKey<E>) -> Result<T, PolymathError> {
+        let mut t = T::new(B_POLYMATH);
+        if let Some(version) = V::VERSION {
+            t.append_message(b"protocol_version", version.to_le_bytes());
+            t.append_message(b"vk_digest", vk.digest()?);
+        }
+        Ok(t)
+    }
+
+    /// Absorb the caller-supplied context `ctx` (if any), the public inputs and the commitments,
+    /// and produce the challenge `x1`.
     pub(crate) fn compute_x1(
         t: &mut T,
+        ctx: Option<&[u8]>,
         public_inputs: &[F],
         commitments: &[E::G1Affine],
     ) -> Result<F, PolymathError> {
-        t.append_message(b"public_inputs", &to_bytes!(&public_inputs)?);
-        t.append_message(b"commitments", &to_bytes!(commitments)?);
+        if let Some(ctx) = ctx {
+            // length-prefixed, so the context can't run into the public inputs
+            t.append_message(b"context", &to_bytes!(&ctx)?);
+        }
+        t.append_scalars(b"public_inputs", public_inputs)?;
+        t.append_points(b"commitments", commitments)?;
 
         Ok(t.challenge(b"x1"))
     }
 
     pub(crate) fn compute_x2(t: &mut T, x1: &F, values: &[F]) -> Result<F, PolymathError> {
-        t.append_message(b"x1", &to_bytes!(x1)?);
-        t.append_message(b"values", &to_bytes!(values)?);
+        t.append_scalar(b"x1", x1)?;
+        t.append_scalars(b"values", values)?;
 
         Ok(t.challenge(b"x2"))
     }
@@ -48,26 +80,24 @@ where
 
     pub(crate) fn compute_pi_at_x1(
         vk: &VerifyingKey<E>,
+        n_inv: F,
+        omega_powers: &[F],
         public_inputs: &[F],
         x1: F,
         y1_gamma: F,
     ) -> F {
-        let mut sum = F::zero();
+        let m0 = vk.m0 as usize;
+        debug_assert_eq!(public_inputs.len(), m0);
 
-        let mut lagrange_i_at_x1_numerator = (x1.pow([vk.n]) - F::one()) / &F::from(vk.n);
-        let mut omega_exp_i = F::one();
+        // Lᵢ(x1) = 𝜔ⁱ·(x1ⁿ - 1)/(n·(x1 - 𝜔ⁱ))
+        let mut denominators: Vec<F> = omega_powers[..m0 * 2].iter().map(|w| x1 - w).collect();
+        batch_inversion(&mut denominators);
 
-        let m0 = public_inputs.len();
+        let sum = (0..m0 * 2)
+            .map(|i| Self::z_tilde_i(public_inputs, i) * omega_powers[i] * denominators[i])
+            .sum::<F>();
 
-        for i in 0..m0 * 2 {
-            let lagrange_i_at_x1 = lagrange_i_at_x1_numerator / (x1 - omega_exp_i);
-            let to_add = Self::z_tilde_i(public_inputs, i) * lagrange_i_at_x1;
-            lagrange_i_at_x1_numerator *= vk.omega;
-            omega_exp_i *= vk.omega;
-            sum += to_add;
-        }
-
-        sum * y1_gamma
+        sum * (x1.pow([vk.n]) - F::one()) * n_inv * y1_gamma
     }
 
     pub(crate) fn compute_c_at_x1(y1_gamma: F, y1_alpha: F, a_at_x1: F, pi_at_x1: F) -> F {
@@ -97,11 +127,149 @@ where
     }
 }
 
-pub(crate) fn m_at<F: Field>(m: &Matrix<F>, i: usize, j: usize) -> F {
-    m[i].iter()
-        .find(|(_, index)| *index == j)
-        .unwrap_or(&(F::zero(), 0))
-        .0
+/// A sparse matrix and its transpose.
+type WithTranspose<F> = (CscMatrix<F>, CscMatrix<F>);
+
+/// Sparse matrix in compressed sparse column (CSC) form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CscMatrix<F: Field> {
+    num_rows: usize,
+    /// Column `j` occupies `column_offsets[j]..column_offsets[j + 1]` in `rows` and `values`.
+    column_offsets: Vec<usize>,
+    /// Row indices of the non-zero elements, ascending within each column.
+    rows: Vec<usize>,
+    values: Vec<F>,
+}
+
+impl<F: Field> CscMatrix<F> {
+    /// Build the matrix and its transpose from its rows, each a list of `(column, value)` pairs.
+    /// Elements in the same cell are added up and zeros are dropped.
+    fn from_rows(num_columns: usize, rows: Vec<Vec<(usize, F)>>) -> WithTranspose<F> {
+        let rows: Vec<Vec<(usize, F)>> = rows
+            .into_iter()
+            .map(|mut row| {
+                row.sort_unstable_by_key(|&(j, _)| j);
+                let mut merged: Vec<(usize, F)> = Vec::with_capacity(row.len());
+                for (j, v) in row {
+                    match merged.last_mut() {
+                        Some((last_j, last_v)) if *last_j == j => *last_v += v,
+                        _ => merged.push((j, v)),
+                    }
+                }
+                merged.retain(|(_, v)| !v.is_zero());
+                merged
+            })
+            .collect();
+
+        // the merged rows are the columns of the transpose, ascending already
+        let transposed = Self {
+            num_rows: num_columns,
+            column_offsets: iter::once(0)
+                .chain(rows.iter().scan(0, |end, row| {
+                    *end += row.len();
+                    Some(*end)
+                }))
+                .collect(),
+            rows: rows.iter().flatten().map(|&(j, _)| j).collect(),
+            values: rows.iter().flatten().map(|&(_, v)| v).collect(),
+        };
+
+        // counting sort by column keeps the rows ascending within each column
+        let mut column_offsets = vec![0; num_columns + 1];
+        for &(j, _) in rows.iter().flatten() {
+            column_offsets[j + 1] += 1;
+        }
+        for j in 0..num_columns {
+            column_offsets[j + 1] += column_offsets[j];
+        }
+
+        let num_non_zeros = column_offsets[num_columns];
+        let mut next = column_offsets[..num_columns].to_vec();
+        let mut row_indices = vec![0; num_non_zeros];
+        let mut values = vec![F::zero(); num_non_zeros];
+        let num_rows = rows.len();
+        for (i, row) in rows.into_iter().enumerate() {
+            for (j, v) in row {
+                row_indices[next[j]] = i;
+                values[next[j]] = v;
+                next[j] += 1;
+            }
+        }
+
+        let matrix = Self {
+            num_rows,
+            column_offsets,
+            rows: row_indices,
+            values,
+        };
+        (matrix, transposed)
+    }
+
+    /// The transposed matrix. Its columns are the rows of `self`.
+    pub fn transpose(&self) -> Self {
+        let num_columns = self.num_rows;
+        let mut column_offsets = vec![0; num_columns + 1];
+        for &i in &self.rows {
+            column_offsets[i + 1] += 1;
+        }
+        for i in 0..num_columns {
+            column_offsets[i + 1] += column_offsets[i];
+        }
+
+        let mut next = column_offsets[..num_columns].to_vec();
+        let mut rows = vec![0; self.num_non_zeros()];
+        let mut values = vec![F::zero(); self.num_non_zeros()];
+        for j in 0..self.num_columns() {
+            for (i, v) in self.column(j) {
+                rows[next[i]] = j;
+                values[next[i]] = v;
+                next[i] += 1;
+            }
+        }
+
+        Self {
+            num_rows: self.num_columns(),
+            column_offsets,
+            rows,
+            values,
+        }
+    }
+
+    /// Number of rows.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Number of columns.
+    pub fn num_columns(&self) -> usize {
+        self.column_offsets.len() - 1
+    }
+
+    /// Number of non-zero elements.
+    pub fn num_non_zeros(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Non-zero elements of column `j` as `(row, value)` pairs, in ascending row order.
+    pub fn column(&self, j: usize) -> impl Iterator<Item = (usize, F)> + '_ {
+        let range = self.column_offsets[j]..self.column_offsets[j + 1];
+        self.rows[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    /// Element at row `i` and column `j`.
+    pub fn get(&self, i: usize, j: usize) -> F {
+        if j >= self.num_columns() {
+            return F::zero();
+        }
+        let range = self.column_offsets[j]..self.column_offsets[j + 1];
+        match self.rows[range.clone()].binary_search(&i) {
+            Ok(k) => self.values[range.start + k],
+            Err(_) => F::zero(),
+        }
+    }
 }
 
 /// SAP (square arithmetic program) matrix representation of underlying R1CS.
@@ -109,24 +277,108 @@ pub(crate) fn m_at<F: Field>(m: &Matrix<F>, i: usize, j: usize) -> F {
 /// R1CS: `Az ∘ Bz = Cz`
 /// We are constructing the views into SAP `U` and `W` matrices from R1CS
 /// preserving the constraints encoded in the underlying R1CS.
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// `U` and `W` are precomputed in sparse column-major form, see [`SAPMatrices::u_csc`],
+/// and so are their transposes for the prover, see [`SAPMatrices::u_rows`].
+/// Only the R1CS matrices are serialized, the SAP matrices are rebuilt on deserialization.
+/// The fields are read-only so `U` and `W` can't go out of sync with the R1CS:
+/// build new matrices with [`SAPMatrices::new`] instead.
+#[derive(Clone, Debug)]
 pub struct SAPMatrices<F: Field> {
+    num_instance_variables: usize,
+    num_r1cs_witness_variables: usize,
+    num_r1cs_constraints: usize,
+
+    a: Matrix<F>,
+    b: Matrix<F>,
+    c: Matrix<F>,
+
+    u_csc: CscMatrix<F>,
+    w_csc: CscMatrix<F>,
+    u_rows: CscMatrix<F>,
+    w_rows: CscMatrix<F>,
+}
+
+impl<F: Field> SAPMatrices<F> {
+    /// Build the SAP matrices for an R1CS with `num_instance_variables` public inputs
+    /// (including the leading `1`), `num_r1cs_witness_variables` private inputs
+    /// and constraint matrices `a`, `b` and `c`.
+    ///
+    /// Fails if the matrices don't have one row per constraint,
+    /// or refer to a variable that doesn't exist.
+    pub fn new(
+        num_instance_variables: usize,
+        num_r1cs_witness_variables: usize,
+        a: Matrix<F>,
+        b: Matrix<F>,
+        c: Matrix<F>,
+    ) -> Result<Self, PolymathError> {
+        let num_r1cs_constraints = a.len();
+        // `size()` and the sparse matrices below rely on these not overflowing
+        let m = num_instance_variables
+            .checked_add(num_r1cs_witness_variables)
+            .filter(|&m| {
+                Self::checked_size(num_instance_variables, m, num_r1cs_constraints).is_some()
+            })
+            .ok_or(PolymathError::InvalidKey("R1CS matrices are too large"))?;
+        let m0_m_n = (num_instance_variables, m, num_r1cs_constraints);
+        if num_instance_variables == 0
+            || b.len() != num_r1cs_constraints
+            || c.len() != num_r1cs_constraints
+            || [&a, &b, &c]
+                .iter()
+                .any(|matrix| matrix.iter().flatten().any(|&(_, j)| j >= m))
+        {
+            return Err(PolymathError::InvalidKey(
+                "R1CS matrices don't match the number of variables",
+            ));
+        }
+
+        let ((u_csc, u_rows), (w_csc, w_rows)) = Self::build_csc(m0_m_n, &a, &b, &c);
+        Ok(Self {
+            num_instance_variables,
+            num_r1cs_witness_variables,
+            num_r1cs_constraints,
+            a,
+            b,
+            c,
+            u_csc,
+            w_csc,
+            u_rows,
+            w_rows,
+        })
+    }
+
     /// Number of public input (a.k.a. instance) variables in the underlying R1CS, including the leading `1`.
-    pub num_instance_variables: usize,
+    pub fn num_instance_variables(&self) -> usize {
+        self.num_instance_variables
+    }
+
     /// Number of private (a.k.a. witness) variables in the underlying R1CS.
-    pub num_r1cs_witness_variables: usize,
+    pub fn num_r1cs_witness_variables(&self) -> usize {
+        self.num_r1cs_witness_variables
+    }
+
     /// Number of constraints in the underlying R1CS.
-    pub num_r1cs_constraints: usize,
+    pub fn num_r1cs_constraints(&self) -> usize {
+        self.num_r1cs_constraints
+    }
 
     /// R1CS `A` matrix.
-    pub a: Vec<Vec<(F, usize)>>,
+    pub fn a(&self) -> &Matrix<F> {
+        &self.a
+    }
+
     /// R1CS `B` matrix.
-    pub b: Vec<Vec<(F, usize)>>,
+    pub fn b(&self) -> &Matrix<F> {
+        &self.b
+    }
+
     /// R1CS `C` matrix.
-    pub c: Vec<Vec<(F, usize)>>,
-}
+    pub fn c(&self) -> &Matrix<F> {
+        &self.c
+    }
 
-impl<F: Field> SAPMatrices<F> {
     /// Number of rows and columns in SAP matrices.
     pub fn size(&self) -> (usize, usize) {
         let (m0, m, n) = self.m0_m_n();
@@ -134,92 +386,116 @@ impl<F: Field> SAPMatrices<F> {
         ((m0 + n) * 2, m0 * 2 + m + n)
     }
 
+    /// What the row `i` of the SAP matrices encodes, or `None` if there's no such row.
+    pub fn row(&self, i: usize) -> Option<SapRow> {
+        let (m0, _, n) = self.m0_m_n();
+        Some(match i {
+            i if i < m0 => SapRow::PublicInputSum { input: i },
+            i if i < 2 * m0 => SapRow::PublicInputDifference { input: i - m0 },
+            i if i < 2 * m0 + n => SapRow::ConstraintSum {
+                constraint: i - 2 * m0,
+            },
+            i if i < 2 * (m0 + n) => SapRow::ConstraintDifference {
+                constraint: i - 2 * m0 - n,
+            },
+            _ => return None,
+        })
+    }
+
     /// Get `Uᵢⱼ` element of the SAP `U` matrix.
     pub fn u(&self, i: usize, j: usize) -> F {
-        let (m0, m, n) = self.m0_m_n();
-        let (double_m0, double_m0_plus_n, double_m0_plus_double_n, m0_plus_m) =
-            Self::inner_size_bounds(m0, m, n);
-
-        let zero = F::zero();
-        let one = F::one();
-        let minus_one = -one;
-        let two = one + one;
+        self.u_csc.get(i, j)
+    }
 
-        match (i, j) {
-            (0, 0) => two,                     // (A₀+1)₀₀=2
-            (i, 0) if i < m0 => one,           // (A₀+1)ᵢ₀=1
-            (i, j) if i < m0 && j == i => one, // (A₀+1)ᵢⱼ=1
+    /// Get `Wᵢⱼ` element of the SAP `W` matrix.
+    pub fn w(&self, i: usize, j: usize) -> F {
+        self.w_csc.get(i, j)
+    }
 
-            (i, _) if i < m0 => zero,
+    /// The SAP `U` matrix in sparse column-major form.
+    pub fn u_csc(&self) -> &CscMatrix<F> {
+        &self.u_csc
+    }
 
-            (i, 0) if i == m0 => zero,      // (A₀-1)₀₀=0
-            (i, 0) if i < double_m0 => one, // (A₀-1)ᵢ₀=1
-            (i, j) if i < double_m0 && j == i - m0 => minus_one, // (A₀-1)ᵢⱼ=-1
+    /// The SAP `W` matrix in sparse column-major form.
+    pub fn w_csc(&self) -> &CscMatrix<F> {
+        &self.w_csc
+    }
 
-            (i, _) if i < double_m0 => zero,
-            (_, j) if j < m0 => zero,
+    /// Rows of the SAP `U` matrix: `Uᵀ` in sparse column-major form.
+    pub fn u_rows(&self) -> &CscMatrix<F> {
+        &self.u_rows
+    }
 
-            (i, j) if i < double_m0_plus_n && j < m0_plus_m => {
-                let (i, j) = (i - double_m0, j - m0);
-                m_at(&self.a, i, j) + m_at(&self.b, i, j)
-            },
-            (i, j) if i < double_m0_plus_double_n && j < m0_plus_m => {
-                let (i, j) = (i - double_m0_plus_n, j - m0);
-                m_at(&self.a, i, j) - m_at(&self.b, i, j)
-            },
-            (..) => zero,
-        }
+    /// Rows of the SAP `W` matrix: `Wᵀ` in sparse column-major form.
+    pub fn w_rows(&self) -> &CscMatrix<F> {
+        &self.w_rows
     }
 
-    /// Get `Wᵢⱼ` element of the SAP `W` matrix.
-    pub fn w(&self, i: usize, j: usize) -> F {
-        let (m0, m, n) = self.m0_m_n();
-        let (double_m0, double_m0_plus_n, double_m0_plus_double_n, m0_plus_m) =
-            Self::inner_size_bounds(m0, m, n);
+    /// Build `U` and `W`, each with its transpose, from the R1CS matrices
+    /// in time proportional to their size.
+    ///
+    /// Rows `[0, m₀)` encode `(1 + xᵢ)² = 4·xᵢ + yᵢ`, rows `[m₀, 2·m₀)` encode `(1 - xᵢ)² = yᵢ`,
+    /// then come `((A + B)·z)² = 4·C·z + y` and `((A - B)·z)² = y` for every R1CS constraint.
+    fn build_csc(
+        (m0, m, n): (usize, usize, usize),
+        a: &Matrix<F>,
+        b: &Matrix<F>,
+        c: &Matrix<F>,
+    ) -> (WithTranspose<F>, WithTranspose<F>) {
+        let num_columns = m0 * 2 + m + n;
+        let (double_m0, double_m0_plus_n, m0_plus_m) = (2 * m0, 2 * m0 + n, m0 + m);
 
-        let zero = F::zero();
         let one = F::one();
         let two = one + one;
         let four = two + two;
 
-        match (i, j) {
-            (i, j) if i < m0 && j == i + m0 => four,
-            (i, j) if i < m0 && j == i + m0_plus_m => one,
-            (i, _) if i < m0 => zero,
-
-            (i, j) if i < double_m0 && j == i + m => one,
-
-            (i, _) if i < double_m0 => zero,
-            (_, j) if j < m0 => zero,
-
-            (i, j) if i < double_m0_plus_n && j < m0_plus_m => {
-                let (i, j) = (i - double_m0, j - m0);
-                m_at(&self.c, i, j) * four
-            },
-
-            (i, j) if i < double_m0_plus_n && j == i + m => one,
-            (i, _) if i < double_m0_plus_n => zero,
-
-            (i, j) if i < double_m0_plus_double_n && j == i - n + m => one,
-
-            (..) => zero,
+        // R1CS columns are shifted by `m₀` in the SAP matrices
+        let shifted = |row: &[(F, usize)], sign: F| -> Vec<(usize, F)> {
+            row.iter().map(|&(v, j)| (j + m0, v * sign)).collect()
+        };
+
+        let mut u_rows = Vec::with_capacity(2 * (m0 + n));
+        let mut w_rows = Vec::with_capacity(2 * (m0 + n));
+
+        // (A₀+1)₀₀=2, (A₀+1)ᵢ₀=1, (A₀+1)ᵢᵢ=1
+        u_rows.push(vec![(0, two)]);
+        u_rows.extend((1..m0).map(|i| vec![(0, one), (i, one)]));
+        // (A₀-1)₀₀=0, (A₀-1)ᵢ₀=1, (A₀-1)ᵢᵢ=-1
+        u_rows.push(vec![]);
+        u_rows.extend((1..m0).map(|i| vec![(0, one), (i, -one)]));
+        for sign in [one, -one] {
+            u_rows.extend(
+                a.iter()
+                    .zip(b)
+                    .map(|(a_i, b_i)| [shifted(a_i, one), shifted(b_i, sign)].concat()),
+            );
         }
-    }
 
-    #[inline]
-    fn inner_size_bounds(m0: usize, m: usize, n: usize) -> (usize, usize, usize, usize) {
-        let double_m0 = m0 + m0;
-        let double_m0_plus_n = double_m0 + n;
-        let double_m0_plus_double_n = double_m0_plus_n + n;
-        let m0_plus_m = m0 + m;
+        w_rows.extend((0..m0).map(|i| vec![(i + m0, four), (i + m0_plus_m, one)]));
+        w_rows.extend((m0..double_m0).map(|i| vec![(i + m, one)]));
+        w_rows.extend(c.iter().enumerate().map(|(i, c_i)| {
+            let mut row = shifted(c_i, four);
+            row.push((double_m0 + i + m, one));
+            row
+        }));
+        w_rows.extend((double_m0_plus_n..double_m0_plus_n + n).map(|i| vec![(i - n + m, one)]));
+
         (
-            double_m0,
-            double_m0_plus_n,
-            double_m0_plus_double_n,
-            m0_plus_m,
+            CscMatrix::from_rows(num_columns, u_rows),
+            CscMatrix::from_rows(num_columns, w_rows),
         )
     }
 
+    /// [`SAPMatrices::size`], or `None` if it overflows
+    /// or leaves no room for the column offsets of the sparse matrices.
+    fn checked_size(m0: usize, m: usize, n: usize) -> Option<(usize, usize)> {
+        let num_rows = m0.checked_add(n)?.checked_mul(2)?;
+        let num_columns = m0.checked_mul(2)?.checked_add(m)?.checked_add(n)?;
+        num_columns.checked_add(1)?;
+        Some((num_rows, num_columns))
+    }
+
     #[inline]
     fn m0_m_n(&self) -> (usize, usize, usize) {
         let m0 = self.num_instance_variables;
@@ -228,3 +504,74 @@ impl<F: Field> SAPMatrices<F> {
         (m0, m, n)
     }
 }
+
+impl<F: Field> CanonicalSerialize for SAPMatrices<F> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.num_instance_variables
+            .serialize_with_mode(&mut writer, compress)?;
+        self.num_r1cs_witness_variables
+            .serialize_with_mode(&mut writer, compress)?;
+        self.num_r1cs_constraints
+            .serialize_with_mode(&mut writer, compress)?;
+        self.a.serialize_with_mode(&mut writer, compress)?;
+        self.b.serialize_with_mode(&mut writer, compress)?;
+        self.c.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.num_instance_variables.serialized_size(compress)
+            + self.num_r1cs_witness_variables.serialized_size(compress)
+            + self.num_r1cs_constraints.serialized_size(compress)
+            + self.a.serialized_size(compress)
+            + self.b.serialized_size(compress)
+            + self.c.serialized_size(compress)
+    }
+}
+
+impl<F: Field> Valid for SAPMatrices<F> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.a.check()?;
+        self.b.check()?;
+        self.c.check()
+    }
+}
+
+impl<F: FftField> CanonicalDeserialize for SAPMatrices<F> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let num_instance_variables =
+            usize::deserialize_with_mode(&mut reader, compress, Validate::No)?;
+        let num_r1cs_witness_variables =
+            usize::deserialize_with_mode(&mut reader, compress, Validate::No)?;
+        let num_r1cs_constraints =
+            usize::deserialize_with_mode(&mut reader, compress, Validate::No)?;
+        let a = Matrix::deserialize_with_mode(&mut reader, compress, validate)?;
+        let b = Matrix::deserialize_with_mode(&mut reader, compress, validate)?;
+        let c = Matrix::deserialize_with_mode(&mut reader, compress, validate)?;
+
+        // the dimensions are always checked: the SAP matrices can't be built otherwise.
+        // The counts are untrusted: besides matching the matrices, they have to fit an evaluation
+        // domain of `F`, which bounds what `Self::new` allocates for them.
+        let fits_domain =
+            |size: usize| Radix2EvaluationDomain::<F>::compute_size_of_domain(size).is_some();
+        let size = num_instance_variables
+            .checked_add(num_r1cs_witness_variables)
+            .and_then(|m| Self::checked_size(num_instance_variables, m, num_r1cs_constraints));
+        match size {
+            Some((num_rows, num_columns))
+                if a.len() == num_r1cs_constraints
+                    && fits_domain(num_rows)
+                    && fits_domain(num_columns) => {},
+            _ => return Err(SerializationError::InvalidData),
+        }
+        Self::new(num_instance_variables, num_r1cs_witness_variables, a, b, c)
+            .map_err(|_| SerializationError::InvalidData)
+    }
+}
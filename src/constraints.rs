@@ -0,0 +1,476 @@
+use ark_crypto_primitives::snark::constraints::{
+    CircuitSpecificSetupSNARKGadget, EmulatedFieldInputVar, SNARKGadget,
+};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{Field, One, PrimeField};
+use ark_r1cs_std::{
+    convert::ToConstraintFieldGadget, fields::emulated_fp::EmulatedFpVar, pairing::PairingVar,
+    prelude::*,
+};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSystemRef, Namespace, SynthesisError},
+};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
+use derivative::Derivative;
+
+use crate::{
+    common::{B_POLYMATH, MINUS_ALPHA, MINUS_GAMMA},
+    to_bytes, FiatShamirV1, FiatShamirVersion, Polymath, PreparedVerifyingKey, Proof, Transcript,
+    VerifyingKey,
+};
+
+type BasePrimeField<E> = <<E as Pairing>::BaseField as Field>::BasePrimeField;
+
+/// Circuit counterpart of a [`Transcript`]: produces the same challenges
+/// from the same messages allocated in a constraint system.
+pub trait TranscriptVar<F: PrimeField, CF: PrimeField>: Sized {
+    /// The native transcript producing the same challenges.
+    type Native: Transcript<Challenge = F>;
+
+    /// Same as [`Transcript::new`].
+    fn new(cs: ConstraintSystemRef<CF>, name: &'static [u8]) -> Result<Self, SynthesisError>;
+
+    /// Same as [`Transcript::append_message`].
+    fn append_message(
+        &mut self,
+        label: &'static [u8],
+        message: &[UInt8<CF>],
+    ) -> Result<(), SynthesisError>;
+
+    /// Same as [`Transcript::append_scalar`].
+    fn append_scalar(
+        &mut self,
+        label: &'static [u8],
+        scalar: &EmulatedFpVar<F, CF>,
+    ) -> Result<(), SynthesisError> {
+        self.append_message(label, &scalar_to_bytes(scalar)?)
+    }
+
+    /// Same as [`Transcript::append_scalars`].
+    fn append_scalars(
+        &mut self,
+        label: &'static [u8],
+        scalars: &[EmulatedFpVar<F, CF>],
+    ) -> Result<(), SynthesisError> {
+        let mut message = UInt8::constant_vec(&(scalars.len() as u64).to_le_bytes());
+        for s in scalars {
+            message.extend(scalar_to_bytes(s)?);
+        }
+        self.append_message(label, &message)
+    }
+
+    /// Same as [`Transcript::append_g1`]: `point` is converted with
+    /// [`ToConstraintFieldGadget`], which gives the affine coordinates and the infinity flag.
+    ///
+    /// Defaults to appending a list of one point, like the native default; implementations
+    /// must override it when their native transcript does too.
+    fn append_g1<G: ToConstraintFieldGadget<CF>>(
+        &mut self,
+        label: &'static [u8],
+        point: &G,
+    ) -> Result<(), SynthesisError> {
+        self.append_points(label, ark_std::slice::from_ref(point))
+    }
+
+    /// Same as [`Transcript::append_points`]: `points` are converted with
+    /// [`ToConstraintFieldGadget`], which gives the affine coordinates and the infinity flag.
+    fn append_points<G: ToConstraintFieldGadget<CF>>(
+        &mut self,
+        label: &'static [u8],
+        points: &[G],
+    ) -> Result<(), SynthesisError>;
+
+    /// Same as [`Transcript::challenge`].
+    fn challenge(&mut self, label: &'static [u8]) -> Result<EmulatedFpVar<F, CF>, SynthesisError>;
+}
+
+/// Bytes of the compressed serialization of `x` (see [`to_bytes!`]).
+fn scalar_to_bytes<F: PrimeField, CF: PrimeField>(
+    x: &EmulatedFpVar<F, CF>,
+) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+    let mut bytes = x.to_bytes_le()?;
+    bytes.truncate(F::zero().compressed_size());
+    Ok(bytes)
+}
+
+/// The proof variable for the Polymath construction.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P::G1Var: Clone"))]
+pub struct ProofVar<E: Pairing, P: PairingVar<E>> {
+    /// `[a]₁` - commitment to `A(X)`.
+    pub a_g1: P::G1Var,
+    /// `[c]₁` - commitment to `C(X)`.
+    pub c_g1: P::G1Var,
+    /// `A(x1)` - evaluation of `A(X)` at point `x1`.
+    pub a_at_x1: EmulatedFpVar<E::ScalarField, BasePrimeField<E>>,
+    /// `[d]₁` - commitment to quotient polynomial `D(X)`.
+    pub d_g1: P::G1Var,
+}
+
+/// The verifying key variable for the Polymath construction.
+///
+/// The domain parameters `n`, `m₀`, `𝜎` and `𝜔` shape the verifier circuit,
+/// so they are constants whatever the allocation mode.
+///
+/// The key can be allocated as a constant or as public input, not as a witness:
+/// its digest is too costly to recompute in the circuit, so a witness key could come with
+/// any digest, and [`FiatShamirV1`] challenges would not be bound to the key.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P::G1Var: Clone, P::G2Var: Clone"))]
+pub struct VerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
+    /// `[1]₁` - the `G1` group generator.
+    pub one_g1: P::G1Var,
+    /// `[1]₂` - the `G2` group generator.
+    pub one_g2: P::G2Var,
+    /// `[x]₂` - the `x` trapdoor hidden in `G2`.
+    pub x_g2: P::G2Var,
+    /// `[z]₂` - the `z` trapdoor hidden in `G2`.
+    pub z_g2: P::G2Var,
+    /// [`VerifyingKey::digest`], absorbed by [`FiatShamirV1`] transcripts.
+    ///
+    /// Only set on allocation, from the same key as the other elements.
+    /// Not constrained to match them: as public input, it is bound to the key
+    /// by the verifier of the outer proof, which computes it from the same key.
+    digest: Vec<UInt8<BasePrimeField<E>>>,
+    /// `n` - the domain size.
+    pub n: u64,
+    /// `m₀` - public input size, including the leading `1`.
+    pub m0: u64,
+    /// `𝜎 = n + 3`.
+    pub sigma: u64,
+    /// `𝜔` - generator of the domain.
+    pub omega: E::ScalarField,
+}
+
+/// Verifying key variable with the `G2` elements prepared for pairing.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "P::G1Var: Clone, P::G2Var: Clone, P::G2PreparedVar: Clone"))]
+pub struct PreparedVerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
+    /// The underlying verifying key.
+    pub vk: VerifyingKeyVar<E, P>,
+    /// `[z]₂` prepared for pairing.
+    pub z_g2: P::G2PreparedVar,
+    /// `[x]₂` prepared for pairing.
+    pub x_g2: P::G2PreparedVar,
+    /// `[1]₂` prepared for pairing.
+    pub one_g2: P::G2PreparedVar,
+}
+
+impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
+    /// Prepare the `G2` elements for pairing.
+    pub fn prepare(&self) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
+        Ok(PreparedVerifyingKeyVar {
+            vk: self.clone(),
+            z_g2: P::prepare_g2(&self.z_g2)?,
+            x_g2: P::prepare_g2(&self.x_g2)?,
+            one_g2: P::prepare_g2(&self.one_g2)?,
+        })
+    }
+}
+
+/// Constraints for the verifier of the Polymath zkSNARK, to verify a proof
+/// inside another circuit, e.g. a BLS12-377 proof in a BW6-761 circuit.
+///
+/// `P` computes the pairings and `TV` recomputes the Fiat–Shamir challenges of
+/// the native transcript `TV::Native`.
+///
+/// Only proofs created with [`PoseidonTranscript`](crate::poseidon::PoseidonTranscript)
+/// can be verified: [`PoseidonTranscriptVar`](crate::poseidon::constraints::PoseidonTranscriptVar)
+/// is the only [`TranscriptVar`] implementation. The hash transcripts (Merlin, Keccak-256,
+/// Blake3, SHA-256) have no circuit counterpart.
+pub struct PolymathVerifierGadget<E, P, TV, V = FiatShamirV1>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    TV: TranscriptVar<E::ScalarField, BasePrimeField<E>>,
+    V: FiatShamirVersion,
+{
+    _p: PhantomData<(E, P, TV, V)>,
+}
+
+impl<E, P, TV, V> PolymathVerifierGadget<E, P, TV, V>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    P::G1Var: ToConstraintFieldGadget<BasePrimeField<E>>,
+    TV: TranscriptVar<E::ScalarField, BasePrimeField<E>>,
+    V: FiatShamirVersion,
+{
+    /// Verify a proof created with [`Polymath::prove_with_context`] and the context `ctx`.
+    pub fn verify_with_context(
+        pvk: &PreparedVerifyingKeyVar<E, P>,
+        public_inputs: &EmulatedFieldInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+        ctx: &[UInt8<BasePrimeField<E>>],
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        Self::verify_proof(pvk, public_inputs, proof, Some(ctx))
+    }
+
+    fn verify_proof(
+        pvk: &PreparedVerifyingKeyVar<E, P>,
+        public_inputs: &EmulatedFieldInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+        ctx: Option<&[UInt8<BasePrimeField<E>>]>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let vk = &pvk.vk;
+        let m0 = vk.m0 as usize;
+        if public_inputs.val.len() + 1 != m0 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let public_inputs = [&[EmulatedFpVar::one()], public_inputs.val.as_slice()].concat();
+
+        let cs = proof.a_g1.cs().or(proof.a_at_x1.cs()).or(vk.z_g2.cs());
+        let mut t = TV::new(cs, B_POLYMATH)?;
+        if let Some(version) = V::VERSION {
+            t.append_message(
+                b"protocol_version",
+                &UInt8::constant_vec(&version.to_le_bytes()),
+            )?;
+            t.append_message(b"vk_digest", &vk.digest)?;
+        }
+
+        // compute challenge x1, with the same messages as `Polymath::compute_x1`
+        if let Some(ctx) = ctx {
+            let mut message = UInt8::constant_vec(&(ctx.len() as u64).to_le_bytes());
+            message.extend_from_slice(ctx);
+            t.append_message(b"context", &message)?;
+        }
+        t.append_scalars(b"public_inputs", &public_inputs)?;
+        t.append_points(b"commitments", &[proof.a_g1.clone(), proof.c_g1.clone()])?;
+        let x1 = t.challenge(b"x1")?;
+
+        // compute y1=x1^sigma
+        let y1 = x1.pow_by_constant([vk.sigma])?;
+        let y1_inv = y1.inverse()?;
+        let y1_alpha = y1_inv.pow_by_constant([MINUS_ALPHA])?;
+        let y1_gamma = y1_inv.pow_by_constant([MINUS_GAMMA])?;
+
+        let pi_at_x1 = Self::compute_pi_at_x1(vk, &public_inputs, &x1, &y1_gamma)?;
+
+        // compute c_at_x1
+        let a_at_x1 = &proof.a_at_x1;
+        let c_at_x1 = ((a_at_x1 + &y1_gamma) * a_at_x1 - &pi_at_x1) * y1_alpha.inverse()?;
+
+        // compute challenge x2, with the same messages as `Polymath::compute_x2`
+        t.append_scalar(b"x1", &x1)?;
+        t.append_scalars(b"values", &[a_at_x1.clone(), c_at_x1.clone()])?;
+        let x2 = t.challenge(b"x2")?;
+
+        // P = [a]₁ + x2·[c]₁ - (A(x1) + x2·C(x1))·[1]₁
+        let eval = a_at_x1 + &x2 * &c_at_x1;
+        let commitments_minus_evals_in_g1 = proof.a_g1.clone()
+            + proof.c_g1.scalar_mul_le(x2.to_bits_le()?.iter())?
+            - vk.one_g1.scalar_mul_le(eval.to_bits_le()?.iter())?;
+        let x1_d_g1 = proof.d_g1.scalar_mul_le(x1.to_bits_le()?.iter())?;
+
+        // e(P, [z]₂) = e([d]₁, [x]₂) - e(x1·[d]₁, [1]₂)
+        let pairing_output = P::product_of_pairings(
+            &[
+                P::prepare_g1(&commitments_minus_evals_in_g1)?,
+                P::prepare_g1(&proof.d_g1.negate()?)?,
+                P::prepare_g1(&x1_d_g1)?,
+            ],
+            &[pvk.z_g2.clone(), pvk.x_g2.clone(), pvk.one_g2.clone()],
+        )?;
+
+        pairing_output.is_one()
+    }
+
+    /// Same as `Polymath::compute_pi_at_x1`, with the domain points as constants.
+    fn compute_pi_at_x1(
+        vk: &VerifyingKeyVar<E, P>,
+        public_inputs: &[EmulatedFpVar<E::ScalarField, BasePrimeField<E>>],
+        x1: &EmulatedFpVar<E::ScalarField, BasePrimeField<E>>,
+        y1_gamma: &EmulatedFpVar<E::ScalarField, BasePrimeField<E>>,
+    ) -> Result<EmulatedFpVar<E::ScalarField, BasePrimeField<E>>, SynthesisError> {
+        let m0 = public_inputs.len();
+        let one = E::ScalarField::one();
+        let n_inv = E::ScalarField::from(vk.n).inverse().unwrap();
+
+        // Lᵢ(x1) = 𝜔ⁱ·(x1ⁿ - 1)/(n·(x1 - 𝜔ⁱ))
+        let mut sum = EmulatedFpVar::zero();
+        let mut omega_i = one;
+        for i in 0..m0 * 2 {
+            let z_tilde_i = match i {
+                0 => EmulatedFpVar::constant(one + one),
+                i if i < m0 => &public_inputs[i] + one,
+                i if i == m0 => EmulatedFpVar::zero(),
+                i => public_inputs[i - m0].negate()? + one,
+            };
+            if i != m0 {
+                sum += z_tilde_i * omega_i * (x1 - omega_i).inverse()?;
+            }
+            omega_i *= vk.omega;
+        }
+
+        Ok(sum * (x1.pow_by_constant([vk.n])? - one) * n_inv * y1_gamma)
+    }
+}
+
+impl<E, P, TV, V> SNARKGadget<E::ScalarField, BasePrimeField<E>, Polymath<E, TV::Native, V>>
+    for PolymathVerifierGadget<E, P, TV, V>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    P::G1Var: ToConstraintFieldGadget<BasePrimeField<E>>,
+    TV: TranscriptVar<E::ScalarField, BasePrimeField<E>>,
+    V: FiatShamirVersion,
+{
+    type ProcessedVerifyingKeyVar = PreparedVerifyingKeyVar<E, P>;
+    type VerifyingKeyVar = VerifyingKeyVar<E, P>;
+    type InputVar = EmulatedFieldInputVar<E::ScalarField, BasePrimeField<E>>;
+    type ProofVar = ProofVar<E, P>;
+
+    /// Number of public inputs, including the leading `1`.
+    type VerifierSize = usize;
+
+    fn verifier_size(circuit_vk: &VerifyingKey<E>) -> Self::VerifierSize {
+        circuit_vk.m0 as usize
+    }
+
+    fn verify_with_processed_vk(
+        circuit_pvk: &Self::ProcessedVerifyingKeyVar,
+        x: &Self::InputVar,
+        proof: &Self::ProofVar,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        Self::verify_proof(circuit_pvk, x, proof, None)
+    }
+
+    fn verify(
+        circuit_vk: &Self::VerifyingKeyVar,
+        x: &Self::InputVar,
+        proof: &Self::ProofVar,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        Self::verify_proof(&circuit_vk.prepare()?, x, proof, None)
+    }
+}
+
+impl<E, P, TV, V>
+    CircuitSpecificSetupSNARKGadget<E::ScalarField, BasePrimeField<E>, Polymath<E, TV::Native, V>>
+    for PolymathVerifierGadget<E, P, TV, V>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    P::G1Var: ToConstraintFieldGadget<BasePrimeField<E>>,
+    TV: TranscriptVar<E::ScalarField, BasePrimeField<E>>,
+    V: FiatShamirVersion,
+{
+}
+
+impl<E: Pairing, P: PairingVar<E>> AllocVar<Proof<E>, BasePrimeField<E>> for ProofVar<E, P> {
+    fn new_variable<T: Borrow<Proof<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        let proof = f().map(|proof| proof.borrow().clone());
+        let a_g1 = P::G1Var::new_variable(
+            ns!(cs, "a_g1"),
+            || proof.as_ref().map(|p| p.a_g1.into_group()).map_err(|e| *e),
+            mode,
+        )?;
+        let c_g1 = P::G1Var::new_variable(
+            ns!(cs, "c_g1"),
+            || proof.as_ref().map(|p| p.c_g1.into_group()).map_err(|e| *e),
+            mode,
+        )?;
+        let a_at_x1 = EmulatedFpVar::new_variable(
+            ns!(cs, "a_at_x1"),
+            || proof.as_ref().map(|p| p.a_at_x1).map_err(|e| *e),
+            mode,
+        )?;
+        let d_g1 = P::G1Var::new_variable(
+            ns!(cs, "d_g1"),
+            || proof.as_ref().map(|p| p.d_g1.into_group()).map_err(|e| *e),
+            mode,
+        )?;
+
+        Ok(ProofVar {
+            a_g1,
+            c_g1,
+            a_at_x1,
+            d_g1,
+        })
+    }
+}
+
+impl<E: Pairing, P: PairingVar<E>> AllocVar<VerifyingKey<E>, BasePrimeField<E>>
+    for VerifyingKeyVar<E, P>
+{
+    /// The key must be available even in setup mode: its domain parameters shape the circuit.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] in [`AllocationMode::Witness`],
+    /// where nothing would bind the digest to the key.
+    fn new_variable<T: Borrow<VerifyingKey<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        if mode == AllocationMode::Witness {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        let vk = f()?.borrow().clone();
+        let digest = vk.digest().map_err(|_| SynthesisError::AssignmentMissing)?;
+
+        Ok(VerifyingKeyVar {
+            one_g1: P::G1Var::new_variable(
+                ns!(cs, "one_g1"),
+                || Ok(vk.e.one_g1.into_group()),
+                mode,
+            )?,
+            one_g2: P::G2Var::new_variable(
+                ns!(cs, "one_g2"),
+                || Ok(vk.e.one_g2.into_group()),
+                mode,
+            )?,
+            x_g2: P::G2Var::new_variable(ns!(cs, "x_g2"), || Ok(vk.e.x_g2.into_group()), mode)?,
+            z_g2: P::G2Var::new_variable(ns!(cs, "z_g2"), || Ok(vk.e.z_g2.into_group()), mode)?,
+            digest: digest
+                .iter()
+                .map(|b| UInt8::new_variable(ns!(cs, "digest"), || Ok(b), mode))
+                .collect::<Result<_, _>>()?,
+            n: vk.n,
+            m0: vk.m0,
+            sigma: vk.sigma,
+            omega: vk.omega,
+        })
+    }
+}
+
+impl<E: Pairing, P: PairingVar<E>> AllocVar<PreparedVerifyingKey<E>, BasePrimeField<E>>
+    for PreparedVerifyingKeyVar<E, P>
+{
+    /// Allocates the underlying key and prepares its `G2` elements in the circuit.
+    fn new_variable<T: Borrow<PreparedVerifyingKey<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let pvk = f()?;
+        VerifyingKeyVar::new_variable(cs, || Ok(&pvk.borrow().vk), mode)?.prepare()
+    }
+}
+
+impl<E: Pairing, P: PairingVar<E>> ToBytesGadget<BasePrimeField<E>> for VerifyingKeyVar<E, P> {
+    fn to_bytes_le(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+        let mut bytes = Vec::new();
+        bytes.extend(self.one_g1.to_bytes_le()?);
+        bytes.extend(self.one_g2.to_bytes_le()?);
+        bytes.extend(self.x_g2.to_bytes_le()?);
+        bytes.extend(self.z_g2.to_bytes_le()?);
+        bytes.extend_from_slice(&self.digest);
+        bytes.extend(UInt8::constant_vec(
+            &to_bytes!(&(self.n, self.m0, self.sigma, self.omega))
+                .map_err(|_| SynthesisError::AssignmentMissing)?,
+        ));
+        Ok(bytes)
+    }
+}
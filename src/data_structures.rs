@@ -1,12 +1,22 @@
 use ark_ec::pairing::Pairing;
-use ark_std::fmt::Debug;
+use ark_ff::{Field, One, Zero};
+use ark_poly::{
+    univariate::{DensePolynomial, SparsePolynomial},
+    Radix2EvaluationDomain,
+};
+use ark_std::{fmt::Debug, io::Read, iter, string::String, vec::Vec};
 
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
 
-use crate::common::SAPMatrices;
+use crate::{common::SAPMatrices, to_bytes};
 
 /// Proof in the Polymath zkSNARK.
-#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// Deserialization with validation runs [`Proof::validate`]: the points must be in the
+/// prime-order subgroup and not the identity.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize)]
 pub struct Proof<E: Pairing> {
     /// `[a]₁` - commitment to `A(X)`.
     pub a_g1: E::G1Affine,
@@ -18,6 +28,32 @@ pub struct Proof<E: Pairing> {
     pub d_g1: E::G1Affine,
 }
 
+impl<E: Pairing> Valid for Proof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.validate().map_err(|_| SerializationError::InvalidData)
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for Proof<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        // `a_at_x1` is always read in its canonical encoding: values `≥ p` are rejected
+        let proof = Proof {
+            a_g1: E::G1Affine::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            c_g1: E::G1Affine::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            a_at_x1: E::ScalarField::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            d_g1: E::G1Affine::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+        };
+        if let Validate::Yes = validate {
+            proof.check()?;
+        }
+        Ok(proof)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Verification key for the pairing check.
@@ -49,6 +85,193 @@ pub struct VerifyingKey<E: Pairing> {
     pub omega: E::ScalarField,
 }
 
+impl<E: Pairing> VerifyingKey<E> {
+    /// `blake3` hash of the compressed serialization of the key:
+    /// `[1]₁`, `[1]₂`, `[x]₂`, `[z]₂`, `n`, `m₀`, `𝜎` and `𝜔`, in this order.
+    ///
+    /// Identifies the circuit and the setup the key is for.
+    pub fn digest(&self) -> Result<[u8; 32], SerializationError> {
+        Ok(blake3::hash(&to_bytes!(self)?).into())
+    }
+
+    /// `1/n` and `(𝜔ⁱ)ᵢ` for `i ∈ [0, 2·m₀)` - constants for evaluating the public input polynomial.
+    pub(crate) fn pi_constants(&self) -> (E::ScalarField, Vec<E::ScalarField>) {
+        let n_inv = E::ScalarField::from(self.n).inverse().unwrap();
+        let omega_powers = iter::successors(Some(E::ScalarField::one()), |w| Some(*w * self.omega))
+            .take(self.m0 as usize * 2)
+            .collect();
+        (n_inv, omega_powers)
+    }
+}
+
+/// Verification key with the `G2` elements prepared for pairing and the constants
+/// for evaluating the public input polynomial precomputed.
+///
+/// Everything but `vk` is derived from `vk`: deserialization checks that the constants match it,
+/// and with validation, that the prepared elements do too.
+#[derive(Clone, Debug, CanonicalSerialize)]
+pub struct PreparedVerifyingKey<E: Pairing> {
+    /// The underlying verification key.
+    pub vk: VerifyingKey<E>,
+    /// `[z]₂` prepared for pairing.
+    pub z_g2: E::G2Prepared,
+    /// `[x]₂` prepared for pairing.
+    pub x_g2: E::G2Prepared,
+    /// `[1]₂` prepared for pairing.
+    pub one_g2: E::G2Prepared,
+    /// `1/n` - inverse of the domain size.
+    pub n_inv: E::ScalarField,
+    /// `[(𝜔ⁱ)ᵢ| i ∈ [0, 2·m₀)]` - domain points the public input polynomial is interpolated over.
+    pub omega_powers: Vec<E::ScalarField>,
+}
+
+impl<E: Pairing> PreparedVerifyingKey<E> {
+    /// Check `1/n` and `(𝜔ⁱ)ᵢ` against `vk`. The verifier indexes `omega_powers` up to `2·m₀`.
+    fn check_constants(&self) -> Result<(), SerializationError> {
+        let vk = &self.vk;
+        let num_omega_powers = vk.m0.checked_mul(2).and_then(|l| usize::try_from(l).ok());
+        if num_omega_powers != Some(self.omega_powers.len())
+            || E::ScalarField::from(vk.n).is_zero()
+            || vk.pi_constants() != (self.n_inv, self.omega_powers.clone())
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> Valid for PreparedVerifyingKey<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.vk.check()?;
+        self.check_constants()?;
+        let vk = &self.vk;
+        for (prepared, point) in [
+            (&self.z_g2, vk.e.z_g2),
+            (&self.x_g2, vk.e.x_g2),
+            (&self.one_g2, vk.e.one_g2),
+        ] {
+            if to_bytes!(prepared)? != to_bytes!(&E::G2Prepared::from(point))? {
+                return Err(SerializationError::InvalidData);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for PreparedVerifyingKey<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let pvk = PreparedVerifyingKey {
+            vk: VerifyingKey::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            z_g2: E::G2Prepared::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            x_g2: E::G2Prepared::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            one_g2: E::G2Prepared::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            n_inv: E::ScalarField::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+            omega_powers: Vec::deserialize_with_mode(&mut reader, compress, Validate::No)?,
+        };
+        // the constants are always checked: the verifier can't use them otherwise
+        pvk.check_constants()?;
+        if let Validate::Yes = validate {
+            pvk.check()?;
+        }
+        Ok(pvk)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// What a row of the SAP matrices encodes.
+///
+/// Public input `xᵢ` gives two rows and so does every R1CS constraint `a·b = c`,
+/// with `y` the auxiliary part of the SAP witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SapRow {
+    /// `(1 + xᵢ)² = 4·xᵢ + yᵢ` for the public input `xᵢ` (`x₀ = 1`).
+    PublicInputSum {
+        /// Index `i` in the instance assignment, including the leading `1`.
+        input: usize,
+    },
+    /// `(1 - xᵢ)² = yᵢ` for the public input `xᵢ` (`x₀ = 1`).
+    PublicInputDifference {
+        /// Index `i` in the instance assignment, including the leading `1`.
+        input: usize,
+    },
+    /// `(a + b)² = 4·c + y` for an R1CS constraint `a·b = c`.
+    ConstraintSum {
+        /// Index of the R1CS constraint.
+        constraint: usize,
+    },
+    /// `(a - b)² = y` for an R1CS constraint `a·b = c`.
+    ConstraintDifference {
+        /// Index of the R1CS constraint.
+        constraint: usize,
+    },
+}
+
+/// A row of the SAP matrices an assignment doesn't satisfy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint {
+    /// Index of the row in the SAP matrices.
+    pub sap_row: usize,
+    /// What the row encodes.
+    pub kind: SapRow,
+    /// Namespace trace of the R1CS constraint.
+    ///
+    /// Only known when the assignment comes from synthesizing the circuit
+    /// with the `ConstraintLayer` of `ark-relations` enabled.
+    pub namespace: Option<String>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Proving key with the circuit-only data the prover needs precomputed,
+/// to be reused across proofs for the same circuit.
+///
+/// It borrows the proving key, so preparing it doesn't copy the key. The rows of `U` and `W`
+/// are cached in the key itself, see [`SAPMatrices::u_rows`].
+/// The FFT twiddle factors are not cached: `ark-poly` computes them inside each transform.
+#[derive(Clone, Debug)]
+pub struct PreparedProvingKey<'a, E: Pairing> {
+    /// The underlying proving key.
+    pub pk: &'a ProvingKey<E>,
+    /// Evaluation domain of the SAP polynomials.
+    pub domain: Radix2EvaluationDomain<E::ScalarField>,
+    /// Domain of twice the size, for squaring `U(X)`.
+    pub squaring_domain: Radix2EvaluationDomain<E::ScalarField>,
+    /// `1/n` - inverse of the domain size.
+    pub n_inv: E::ScalarField,
+    /// `[(𝜔ⁱ)ᵢ| i ∈ [0, 2·m₀)]` - domain points the public input polynomial is interpolated over.
+    pub omega_powers: Vec<E::ScalarField>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The witness-dependent part of a Polymath proof, to create many proofs of the same statement
+/// with [`Polymath::prove_with_witness_polynomials`](crate::Polymath::prove_with_witness_polynomials).
+///
+/// Holds the SAP polynomial `U(X)` and the commitments that don't depend on the blinding
+/// polynomial `r_a(X)`: only `r_a(X)`, the challenges and `D(X)` are computed for each proof.
+///
+/// Reveals the witness: keep it as secret as the witness itself.
+#[derive(Clone, Debug)]
+pub struct WitnessPolynomials<E: Pairing> {
+    /// Instance assignment `x`, including the leading `1`.
+    pub instance_assignment: Vec<E::ScalarField>,
+    /// `U(X)` - the SAP polynomial `∑ⱼ zⱼ·uⱼ(X)`.
+    pub u_poly: DensePolynomial<E::ScalarField>,
+    /// `[U(x)]₁`.
+    pub u_g1: E::G1Affine,
+    /// `[x·U(x)]₁`.
+    pub x_u_g1: E::G1Affine,
+    /// `[c]₁` without the terms from `r_a(X)`.
+    pub c_without_r_a_g1: E::G1Affine,
+    /// `C(X)·(Y^-𝛾)` without the terms from `r_a(X)`.
+    pub c_without_r_a_x_by_y_gamma_poly: SparsePolynomial<E::ScalarField>,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Proving key for the Polymath zkSNARK.
@@ -1,25 +1,72 @@
+//! # Trusted setup
+//!
+//! The proving key is derived from two trapdoors: `x` and `z`. With `y = x^𝜎`, `y^𝛼 = x^(-3𝜎)`
+//! and `y^𝛾 = x^(-5𝜎)`, it holds:
+//!
+//! | vector                      | elements                                           | `j`                   |
+//! |-----------------------------|----------------------------------------------------|-----------------------|
+//! | `x_powers_g1`               | `[xʲ]₁`                                            | `[0, n]`              |
+//! | `x_powers_y_alpha_g1`       | `[x^(j-3𝜎)]₁`                                      | `[0, 2]`              |
+//! | `x_powers_y_gamma_g1`       | `[x^(j-5𝜎)]₁`                                      | `[0, 1]`              |
+//! | `x_powers_y_gamma_z_g1`     | `[x^(j-5𝜎)·z]₁`                                    | `[0, 2n - 2 + 8𝜎]`    |
+//! | `x_powers_zh_by_y_alpha_g1` | `[Z_H(x)·x^(j+3𝜎)]₁`                               | `[0, n - 2]`          |
+//! | `uj_wj_lcs_by_y_alpha_g1`   | `[uⱼ(x)·x^(-2𝜎) + wⱼ(x)·x^(3𝜎)]₁`                  | witness columns       |
+//!
+//! and `[1]₂`, `[x]₂` and `[z]₂` in the verifying key.
+//!
+//! Anybody who has `[x^(k-2𝜎)]₁` for `k ∈ [0, n)` can forge proofs for any public input. These
+//! are the monomials of `uⱼ(x)·x^(-2𝜎)` for the public input columns, which the key leaves out.
+//! With `A(X) = X^(-3𝜎)` and `C(X) = X^(-3𝜎) + X^(-5𝜎) - PI(X)·X^(-2𝜎)`, the value of `C(x1)`
+//! the verifier computes from `A(x1)` is right for every `x1`, and the quotient behind `[d]₁`
+//! only has exponents in `[-5𝜎, n - 2 - 2𝜎]`, which `x_powers_y_gamma_z_g1` covers. Either
+//! trapdoor gives these powers: `x` directly, `z` by dividing `x_powers_y_gamma_z_g1` by it.
+//! `tests/trusted_setup.rs` forges proofs of false statements both ways.
+//!
+//! Unlike Groth16, the key cannot be produced by a multi-party ceremony that rerandomizes
+//! an existing key. Rerandomizing `z` alone, by scaling `x_powers_y_gamma_z_g1` and `[z]₂`
+//! by a contribution, is possible but leaves whoever sampled `x` able to forge. Rerandomizing
+//! `x` to `x·𝛿` has to map each `[f(x)]₁` to `[f(x·𝛿)]₁`. Without `x`, a contributor can only
+//! combine published elements with coefficients depending on `𝛿`, so `f(𝛿·X)` has to be
+//! a combination of the published polynomials. This holds for monomials, `f(𝛿·X) = 𝛿ᵉ·f(X)`,
+//! but in general not for `uⱼ(X)·X^(-2𝜎)`: `uⱼ(X)` combines Lagrange polynomials
+//! `Lᵢ(X) = (𝜔ⁱ/n)·∑ₖ 𝜔^(-i(k+1))·Xᵏ`, all `n` coefficients of which are non-zero. Updating
+//! `uj_wj_lcs_by_y_alpha_g1` takes the individual `[x^(k-2𝜎)]₁`, which are the powers
+//! that forge proofs. Groth16 divides its circuit-specific elements by an independent trapdoor
+//! `𝛿`, and a second phase rerandomizes it. Here they are divided by `y^𝛼`, a power of `x`.
+//!
+//! A public powers-of-tau transcript (e.g. a snarkjs `.ptau` file) can't serve as the source
+//! of `x` either. It publishes `[τⁱ]₁` for `i ∈ [0, N)` and `[τⁱ]₂` for fewer `i`. The key
+//! needs the exponents of `x` from `-5𝜎` to `2n - 2 + 3𝜎`, the range of
+//! `x_powers_y_gamma_z_g1`. The transcript only has non-negative ones, so it can only be used
+//! with `x = τ` on the shifted base `[τ^(5𝜎)]₁` as `[1]₁`, which takes `N > 2n - 2 + 8𝜎`.
+//! Then the transcript's `[τ^(k+3𝜎)]₁` is `[x^(k-2𝜎)]₁`, and every prover has the powers that
+//! forge proofs; `tests/trusted_setup.rs` forges one from such a transcript. Importing only
+//! `x_powers_g1` and `[x]₂` doesn't remove the trust either: the other vectors still take `x`.
+//!
+//! Keys must come from
+//! [`Polymath::setup`](ark_crypto_primitives::snark::CircuitSpecificSetupSNARK::setup)
+//! run by a trusted party that discards `x` and `z`.
+
 use ark_ec::{pairing::Pairing, CurveGroup, PrimeGroup};
 use ark_ff::PrimeField;
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError, SynthesisMode,
 };
-use ark_std::{cfg_into_iter, rand::RngCore};
-
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
+use ark_std::{rand::RngCore, vec::Vec};
 
 use crate::{
-    common::{SAPMatrices, MINUS_ALPHA, MINUS_GAMMA},
-    PairingVK, Polymath, PolymathError, ProvingKey, Transcript, VerifyingKey,
+    common::{SAPMatrices, BND_A, MINUS_ALPHA, MINUS_GAMMA},
+    FiatShamirVersion, PairingVK, Polymath, PolymathError, ProvingKey, Transcript, VerifyingKey,
 };
 
 type D<F> = Radix2EvaluationDomain<F>;
 
-impl<F: PrimeField, E: Pairing, T> Polymath<E, T>
+impl<F: PrimeField, E: Pairing, T, V> Polymath<E, T, V>
 where
     E: Pairing<ScalarField = F>,
     T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
 {
     pub(crate) fn generate_proving_key<C: ConstraintSynthesizer<F>, R: RngCore>(
         circuit: C,
@@ -43,22 +90,23 @@ where
 
         ///////////////////////////////////////////////////////////////////////////
 
-        let r1cs_matrices = cs.to_matrices().unwrap();
-        let sap_matrices = SAPMatrices {
-            num_instance_variables: r1cs_matrices.num_instance_variables,
-            num_r1cs_witness_variables: r1cs_matrices.num_witness_variables,
-            num_r1cs_constraints: r1cs_matrices.num_constraints,
-            a: r1cs_matrices.a,
-            b: r1cs_matrices.b,
-            c: r1cs_matrices.c,
-        };
+        let r1cs_matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+        let sap_matrices = SAPMatrices::new(
+            r1cs_matrices.num_instance_variables,
+            r1cs_matrices.num_witness_variables,
+            r1cs_matrices.a,
+            r1cs_matrices.b,
+            r1cs_matrices.c,
+        )?;
 
         ///////////////////////////////////////////////////////////////////////////
 
         let domain_time = start_timer!(|| "Constructing evaluation domain");
 
         let (num_constraints, num_columns) = sap_matrices.size(); // (rows, columns) in U and W matrices
-        let domain = D::new(num_constraints).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let domain = D::new(num_constraints).ok_or(PolymathError::DomainTooLarge {
+            size: num_constraints,
+        })?;
 
         end_timer!(domain_time);
         ///////////////////////////////////////////////////////////////////////////
@@ -66,7 +114,7 @@ where
         let n = domain.size(); // a power of 2
         let m = num_columns;
         let m0 = cs.num_instance_variables();
-        let bnd_a: usize = 1;
+        let bnd_a = BND_A;
         let sigma = n + 3;
 
         let x: F = domain.sample_element_outside_domain(rng);
@@ -112,24 +160,14 @@ where
         let uj_wj_lcs_by_y_alpha_g1 = {
             let l_at_x = domain.evaluate_all_lagrange_coefficients(x);
 
+            let u = sap_matrices.u_csc();
+            let w = sap_matrices.w_csc();
+
+            // `uⱼ(x) = ∑ᵢ Uᵢⱼ·Lᵢ(x)`: only the non-zero elements of column `j` contribute
             Self::generate(g1, m - m0 - 1, |j| {
-                let uj_evals: Vec<F> = cfg_into_iter!(0..n)
-                    .map(|i| sap_matrices.u(i, j as usize + m0))
-                    .collect();
-                debug_assert_eq!(uj_evals.len(), l_at_x.len());
-                let wj_evals: Vec<F> = cfg_into_iter!(0..n)
-                    .map(|i| sap_matrices.w(i, j as usize + m0))
-                    .collect();
-                debug_assert_eq!(wj_evals.len(), l_at_x.len());
-
-                let uj_x = cfg_iter!(l_at_x)
-                    .zip(uj_evals)
-                    .map(|(&l, uj)| l * &uj)
-                    .sum::<F>();
-                let wj_x = cfg_iter!(l_at_x)
-                    .zip(wj_evals)
-                    .map(|(&l, wj)| l * &wj)
-                    .sum::<F>();
+                let j = j as usize + m0;
+                let uj_x = u.column(j).map(|(i, uij)| l_at_x[i] * uij).sum::<F>();
+                let wj_x = w.column(j).map(|(i, wij)| l_at_x[i] * wij).sum::<F>();
 
                 (uj_x * &y_gamma + wj_x) * &y_to_minus_alpha
             })
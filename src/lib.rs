@@ -15,49 +15,80 @@
 #[macro_use]
 extern crate ark_std;
 
+#[cfg(feature = "prover")]
 use ark_crypto_primitives::snark::*;
 use ark_ec::pairing::Pairing;
+#[cfg(feature = "prover")]
 use ark_ff::PrimeField;
-use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+#[cfg(feature = "prover")]
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_relations::r1cs::SynthesisError;
 use ark_serialize::SerializationError;
-use ark_std::{clone::Clone, fmt::Debug, marker::PhantomData, rand::RngCore, result::Result};
-
-pub use self::{data_structures::*, transcript::*};
+use ark_std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+};
+#[cfg(feature = "prover")]
+use ark_std::{rand::RngCore, result::Result};
+
+pub use self::{
+    common::{CscMatrix, SAPMatrices},
+    data_structures::*,
+    transcript::*,
+};
 
 /// Data structures used by the prover, verifier, and generator.
 pub mod data_structures;
 
 /// Generate public parameters for the Polymath zkSNARK construction.
+#[cfg(feature = "prover")]
 pub mod generator;
 
 /// Create proofs for the Polymath zkSNARK construction.
+#[cfg(feature = "prover")]
 pub mod prover;
 
 /// Verify proofs for the Polymath zkSNARK construction.
 pub mod verifier;
 
+/// Constraints for verifying Polymath proofs in a circuit.
+#[cfg(feature = "r1cs")]
+pub mod constraints;
+
+/// Solidity verifier for Polymath proofs over BN254.
+#[cfg(feature = "solidity")]
+pub mod solidity;
+
 mod common;
 mod r#macro;
 pub mod transcript;
+mod validation;
 
 /// The [Polymath](https://eprint.iacr.org/2024/916.pdf) zkSNARK.
-pub struct Polymath<E, T>
+///
+/// `V` selects the Fiat–Shamir transcript layout, see [`FiatShamirVersion`].
+pub struct Polymath<E, T, V = FiatShamirV1>
 where
     E: Pairing,
     T: Transcript<Challenge = E::ScalarField>,
+    V: FiatShamirVersion,
 {
-    _p: PhantomData<(E, T)>,
+    _p: PhantomData<(E, T, V)>,
 }
 
-impl<F: PrimeField, E, T> SNARK<F> for Polymath<E, T>
+/// Requires the `prover` feature: verifier-only builds verify proofs with the inherent methods
+/// of [`Polymath`] instead.
+#[cfg(feature = "prover")]
+impl<F: PrimeField, E, T, V> SNARK<F> for Polymath<E, T, V>
 where
     E: Pairing<ScalarField = F>,
     T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
 {
     type ProvingKey = ProvingKey<E>;
     type VerifyingKey = VerifyingKey<E>;
     type Proof = Proof<E>;
-    type ProcessedVerifyingKey = VerifyingKey<E>;
+    type ProcessedVerifyingKey = PreparedVerifyingKey<E>;
     type Error = PolymathError;
 
     fn circuit_specific_setup<C: ConstraintSynthesizer<F>, R: RngCore>(
@@ -74,11 +105,11 @@ where
         circuit: C,
         rng: &mut R,
     ) -> Result<Self::Proof, Self::Error> {
-        Self::create_proof(circuit, pk, rng)
+        Self::create_proof(circuit, &Self::prepare_proving_key(pk)?, None, rng)
     }
 
     fn process_vk(vk: &Self::VerifyingKey) -> Result<Self::ProcessedVerifyingKey, Self::Error> {
-        Ok(vk.clone())
+        Ok(Self::prepare_verifying_key(vk))
     }
 
     fn verify_with_processed_vk(
@@ -86,24 +117,132 @@ where
         x: &[F],
         proof: &Self::Proof,
     ) -> Result<bool, Self::Error> {
-        Self::verify_proof(vk, proof, x)
+        Self::verify_proof(vk, proof, x, None)
     }
 }
 
-impl<F: PrimeField, E, T> CircuitSpecificSetupSNARK<F> for Polymath<E, T>
+#[cfg(feature = "prover")]
+impl<F: PrimeField, E, T, V> CircuitSpecificSetupSNARK<F> for Polymath<E, T, V>
 where
     E: Pairing<ScalarField = F>,
     T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
 {
 }
 
 /// Error types for Polymath.
-#[derive(thiserror::Error, Debug)]
+#[derive(Debug)]
 pub enum PolymathError {
     /// Wraps `SynthesisError`.
-    #[error(transparent)]
-    SynthesisError(#[from] SynthesisError),
+    SynthesisError(SynthesisError),
     /// Wraps `SerializationError`.
-    #[error(transparent)]
-    SerializationError(#[from] SerializationError),
+    SerializationError(SerializationError),
+    /// The number of public inputs doesn't match the verifying key.
+    ///
+    /// Both counts leave out the leading `1` of the instance assignment, like the public inputs
+    /// passed to the verifier.
+    PublicInputLengthMismatch {
+        /// Number of public inputs the verifying key is for.
+        expected: usize,
+        /// Number of public inputs provided.
+        got: usize,
+    },
+    /// The number of witness variables doesn't match the proving key.
+    WitnessLengthMismatch {
+        /// Number of witness variables in the circuit the proving key is for.
+        expected: usize,
+        /// Number of witness variables provided.
+        got: usize,
+    },
+    /// The scalar field has no evaluation domain large enough for the circuit.
+    DomainTooLarge {
+        /// Minimal size of the domain.
+        size: usize,
+    },
+    /// The assignment doesn't satisfy the constraints of the circuit.
+    UnsatisfiedConstraints,
+    /// A proof element is malformed.
+    InvalidProofElement {
+        /// Name of the proof element.
+        element: &'static str,
+        /// What is wrong with it.
+        reason: &'static str,
+    },
+    /// A key vector doesn't have the length implied by the key parameters.
+    InvalidKeyLength {
+        /// Name of the key vector.
+        name: &'static str,
+        /// Length implied by the key parameters.
+        expected: usize,
+        /// Actual length.
+        got: usize,
+    },
+    /// A key element or parameter fails a consistency check.
+    InvalidKey(&'static str),
+    /// A proof in a batch doesn't verify.
+    BatchVerificationFailed {
+        /// Index of the first invalid proof in the batch.
+        index: usize,
+    },
+}
+
+impl fmt::Display for PolymathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolymathError::SynthesisError(e) => fmt::Display::fmt(e, f),
+            PolymathError::SerializationError(e) => fmt::Display::fmt(e, f),
+            PolymathError::PublicInputLengthMismatch { expected, got } => {
+                write!(f, "expected {} public inputs, got {}", expected, got)
+            },
+            PolymathError::WitnessLengthMismatch { expected, got } => {
+                write!(f, "expected {} witness variables, got {}", expected, got)
+            },
+            PolymathError::DomainTooLarge { size } => {
+                write!(
+                    f,
+                    "the scalar field has no evaluation domain of size {}",
+                    size
+                )
+            },
+            PolymathError::UnsatisfiedConstraints => {
+                write!(f, "the assignment doesn't satisfy the constraints")
+            },
+            PolymathError::InvalidProofElement { element, reason } => {
+                write!(f, "invalid proof element `{}`: {}", element, reason)
+            },
+            PolymathError::InvalidKeyLength {
+                name,
+                expected,
+                got,
+            } => write!(f, "`{}` has length {}, expected {}", name, got, expected),
+            PolymathError::InvalidKey(reason) => write!(f, "invalid key: {}", reason),
+            PolymathError::BatchVerificationFailed { index } => {
+                write!(f, "proof {} in the batch is invalid", index)
+            },
+        }
+    }
+}
+
+// `ark_std::error::Error` is `std::error::Error` with `std`, and a stand-in trait without it.
+// The wrapped errors are transparent: their message is the message of `PolymathError`.
+impl ark_std::error::Error for PolymathError {
+    fn source(&self) -> Option<&(dyn ark_std::error::Error + 'static)> {
+        match self {
+            PolymathError::SynthesisError(e) => e.source(),
+            PolymathError::SerializationError(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<SynthesisError> for PolymathError {
+    fn from(e: SynthesisError) -> Self {
+        PolymathError::SynthesisError(e)
+    }
+}
+
+impl From<SerializationError> for PolymathError {
+    fn from(e: SerializationError) -> Self {
+        PolymathError::SerializationError(e)
+    }
 }
@@ -5,31 +5,234 @@ use ark_poly::{
     DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain,
 };
 use ark_relations::r1cs::{
-    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError, SynthesisMode,
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal, SynthesisError,
+    SynthesisMode,
 };
-use ark_std::{cfg_into_iter, iterable::Iterable, ops::Mul, rand::RngCore, Zero};
+use ark_std::{cfg_into_iter, cfg_iter, ops::Mul, rand::RngCore, vec::Vec, Zero};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use crate::{
-    common::{m_at, B_POLYMATH, MINUS_ALPHA, MINUS_GAMMA},
-    Polymath, PolymathError, Proof, ProvingKey, Transcript,
+    common::{CscMatrix, MINUS_ALPHA, MINUS_GAMMA},
+    FiatShamirVersion, Polymath, PolymathError, PreparedProvingKey, Proof, ProvingKey, Transcript,
+    WitnessPolynomials,
 };
 
 type D<F> = Radix2EvaluationDomain<F>;
 
-impl<F: PrimeField, E, T> Polymath<E, T>
+impl<F: PrimeField, E, T, V> Polymath<E, T, V>
 where
     E: Pairing<ScalarField = F>,
     T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
 {
-    pub(crate) fn create_proof<C: ConstraintSynthesizer<F>, R: RngCore>(
+    /// Create a Polymath proof for `circuit` bound to the context `ctx`,
+    /// e.g. the transaction the proof authorizes.
+    ///
+    /// `ctx` is absorbed into the transcript before the challenge `x1`, which makes the proof
+    /// a signature of knowledge on `ctx` without adding public inputs to the circuit.
+    /// Verify it with [`Polymath::verify_with_context`] and the same `ctx`.
+    pub fn prove_with_context<C: ConstraintSynthesizer<F>, R: RngCore>(
+        pk: &ProvingKey<E>,
         circuit: C,
+        ctx: &[u8],
+        rng: &mut R,
+    ) -> Result<Proof<E>, PolymathError> {
+        Self::create_proof(circuit, &Self::prepare_proving_key(pk)?, Some(ctx), rng)
+    }
+
+    /// Create a Polymath proof from an assignment computed without running
+    /// [`ConstraintSynthesizer::generate_constraints`], e.g. by an external witness generator.
+    ///
+    /// `instance` holds the public inputs without the leading `1`, the same as passed to
+    /// the verifier, and `witness` holds the R1CS witness variables in allocation order.
+    /// Their lengths are checked against the SAP matrices in `pk`.
+    ///
+    /// The assignment is not checked to satisfy the constraints.
+    pub fn prove_with_assignment<R: RngCore>(
         pk: &ProvingKey<E>,
+        instance: &[F],
+        witness: &[F],
+        rng: &mut R,
+    ) -> Result<Proof<E>, PolymathError> {
+        let ppk = Self::prepare_proving_key(pk)?;
+        Self::prove_prepared_with_assignment(&ppk, instance, witness, None, rng)
+    }
+
+    /// Precompute the circuit-only data the prover needs, to reuse it across proofs.
+    ///
+    /// Fails if the key parameters don't match its SAP matrices or a key vector
+    /// has the wrong length (see [`ProvingKey::validate`] for the full check).
+    pub fn prepare_proving_key(
+        pk: &ProvingKey<E>,
+    ) -> Result<PreparedProvingKey<'_, E>, PolymathError> {
+        let domain = pk.check_shape()?;
+        let squaring_domain = D::new(domain.size() * 2).ok_or(PolymathError::DomainTooLarge {
+            size: domain.size() * 2,
+        })?;
+        let (n_inv, omega_powers) = pk.vk.pi_constants();
+
+        Ok(PreparedProvingKey {
+            pk,
+            domain,
+            squaring_domain,
+            n_inv,
+            omega_powers,
+        })
+    }
+
+    /// Create a Polymath proof for `circuit` with the prepared proving key `ppk`,
+    /// optionally bound to the context `ctx` (see [`Polymath::prove_with_context`]).
+    pub fn prove_prepared<C: ConstraintSynthesizer<F>, R: RngCore>(
+        ppk: &PreparedProvingKey<'_, E>,
+        circuit: C,
+        ctx: Option<&[u8]>,
+        rng: &mut R,
+    ) -> Result<Proof<E>, PolymathError> {
+        Self::create_proof(circuit, ppk, ctx, rng)
+    }
+
+    /// Same as [`Polymath::prove_with_assignment`] with the prepared proving key `ppk`,
+    /// optionally bound to the context `ctx` (see [`Polymath::prove_with_context`]).
+    pub fn prove_prepared_with_assignment<R: RngCore>(
+        ppk: &PreparedProvingKey<'_, E>,
+        instance: &[F],
+        witness: &[F],
+        ctx: Option<&[u8]>,
+        rng: &mut R,
+    ) -> Result<Proof<E>, PolymathError> {
+        let wp = Self::compute_witness_polynomials_with_assignment(ppk, instance, witness)?;
+        Self::prove_with_witness_polynomials(ppk, &wp, ctx, rng)
+    }
+
+    /// Compute the witness-dependent part of a proof for `circuit`
+    /// with the prepared proving key `ppk`, see [`WitnessPolynomials`].
+    pub fn compute_witness_polynomials<C: ConstraintSynthesizer<F>>(
+        ppk: &PreparedProvingKey<'_, E>,
+        circuit: C,
+    ) -> Result<WitnessPolynomials<E>, PolymathError> {
+        let cs = Self::synthesize(circuit)?;
+        let prover = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+
+        Self::witness_polynomials(ppk, &prover.instance_assignment, &prover.witness_assignment)
+    }
+
+    /// Same as [`Polymath::compute_witness_polynomials`] from an assignment,
+    /// see [`Polymath::prove_with_assignment`] for the layout and the length checks.
+    pub fn compute_witness_polynomials_with_assignment(
+        ppk: &PreparedProvingKey<'_, E>,
+        instance: &[F],
+        witness: &[F],
+    ) -> Result<WitnessPolynomials<E>, PolymathError> {
+        let sap_matrices = &ppk.pk.sap_matrices;
+        if instance.len() + 1 != sap_matrices.num_instance_variables() {
+            return Err(PolymathError::PublicInputLengthMismatch {
+                expected: sap_matrices.num_instance_variables().saturating_sub(1),
+                got: instance.len(),
+            });
+        }
+        if witness.len() != sap_matrices.num_r1cs_witness_variables() {
+            return Err(PolymathError::WitnessLengthMismatch {
+                expected: sap_matrices.num_r1cs_witness_variables(),
+                got: witness.len(),
+            });
+        }
+
+        let instance_assignment = [&[F::one()], instance].concat();
+        Self::witness_polynomials(ppk, &instance_assignment, witness)
+    }
+
+    /// Create a fresh Polymath proof from the witness polynomials `wp`, computed with the same
+    /// prepared proving key `ppk`, optionally bound to the context `ctx`
+    /// (see [`Polymath::prove_with_context`]).
+    ///
+    /// This is the fast path for creating many unlinkable proofs of the same statement:
+    /// it samples a new blinding polynomial `r_a(X)` and skips synthesis, the SAP evaluations,
+    /// the FFTs and the quotient `H(X)`. Its output is distributed as that of [`SNARK::prove`].
+    ///
+    /// An existing proof can't be rerandomized without the witness polynomials:
+    /// the challenge `x₁` is derived from `[a]₁` and `[c]₁`, so re-blinding them moves `x₁`,
+    /// and the new evaluation `A(x₁)` and opening `[d]₁` need `U(X)`.
+    ///
+    /// Fails with [`PolymathError::PublicInputLengthMismatch`] if `wp` has the wrong number of
+    /// public inputs, and with [`PolymathError::InvalidKeyLength`] if one of its polynomials
+    /// is longer than the key powers it is committed with.
+    ///
+    /// [`SNARK::prove`]: ark_crypto_primitives::snark::SNARK::prove
+    pub fn prove_with_witness_polynomials<R: RngCore>(
+        ppk: &PreparedProvingKey<'_, E>,
+        wp: &WitnessPolynomials<E>,
+        ctx: Option<&[u8]>,
+        rng: &mut R,
+    ) -> Result<Proof<E>, PolymathError> {
+        Self::check_witness_polynomials(ppk, wp)?;
+
+        Self::create_proof_with_witness_polynomials(ppk, wp, ctx, rng)
+    }
+
+    /// Check the lengths of the fields of `wp` against the proving key,
+    /// so the proof commits to all coefficients of the polynomials derived from them.
+    fn check_witness_polynomials(
+        ppk: &PreparedProvingKey<'_, E>,
+        wp: &WitnessPolynomials<E>,
+    ) -> Result<(), PolymathError> {
+        let pk = ppk.pk;
+        let n = ppk.domain.size();
+        let num_instance_variables = pk.sap_matrices.num_instance_variables();
+        if wp.instance_assignment.len() != num_instance_variables {
+            // the error counts the public inputs without the leading `1`
+            return Err(PolymathError::PublicInputLengthMismatch {
+                expected: num_instance_variables - 1,
+                got: wp.instance_assignment.len().saturating_sub(1),
+            });
+        }
+
+        // `U(X)` is interpolated over the domain
+        if wp.u_poly.coeffs.len() > n {
+            return Err(PolymathError::InvalidKeyLength {
+                name: "u_poly",
+                expected: n,
+                got: wp.u_poly.coeffs.len(),
+            });
+        }
+        // `D(X)·(Y^-𝛾)` is at most one degree lower than `C(X)·(Y^-𝛾)`,
+        // and is committed with `[(xⁱ·y^𝛾·z)ᵢ]₁`
+        let c_poly = &wp.c_without_r_a_x_by_y_gamma_poly;
+        let c_len = if c_poly.is_zero() {
+            0
+        } else {
+            c_poly.degree() + 1
+        };
+        if c_len > pk.x_powers_y_gamma_z_g1.len() {
+            return Err(PolymathError::InvalidKeyLength {
+                name: "c_without_r_a_x_by_y_gamma_poly",
+                expected: pk.x_powers_y_gamma_z_g1.len(),
+                got: c_len,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn create_proof<C: ConstraintSynthesizer<F>, R: RngCore>(
+        circuit: C,
+        ppk: &PreparedProvingKey<'_, E>,
+        ctx: Option<&[u8]>,
         rng: &mut R,
     ) -> Result<Proof<E>, PolymathError> {
         let prover_time = start_timer!(|| "Polymath::Prover");
+        let wp = Self::compute_witness_polynomials(ppk, circuit)?;
+        let proof = Self::create_proof_with_witness_polynomials(ppk, &wp, ctx, rng)?;
+
+        end_timer!(prover_time);
+
+        Ok(proof)
+    }
+
+    /// Synthesize `circuit` to produce its assignment, without generating the matrices.
+    pub(crate) fn synthesize<C: ConstraintSynthesizer<F>>(
+        circuit: C,
+    ) -> Result<ConstraintSystemRef<F>, PolymathError> {
         let cs = ConstraintSystem::new_ref();
 
         // Set the optimization goal
@@ -42,88 +245,152 @@ where
         // Synthesize the circuit.
         let synthesis_time = start_timer!(|| "Constraint synthesis");
         circuit.generate_constraints(cs.clone())?;
-        // debug_assert!(cs.is_satisfied().unwrap());
         end_timer!(synthesis_time);
 
         let lc_time = start_timer!(|| "Inlining LCs");
         cs.finalize();
         end_timer!(lc_time);
 
-        let prover = cs.borrow().unwrap();
-
-        let proof = Self::create_proof_with_assignment(
-            pk,
-            &prover.instance_assignment,
-            &prover.witness_assignment,
-            rng,
-        )?;
-
-        end_timer!(prover_time);
-
-        Ok(proof)
+        Ok(cs)
     }
 
-    fn create_proof_with_assignment<R: RngCore>(
+    /// Check the lengths of the instance assignment (including the leading `1`)
+    /// and the witness assignment against the SAP matrices in `pk`.
+    pub(crate) fn check_assignment_lengths(
         pk: &ProvingKey<E>,
         instance_assignment: &[F],
         witness_assignment: &[F],
-        rng: &mut R,
-    ) -> Result<Proof<E>, PolymathError>
-    where
-        T: Transcript<Challenge = F>,
-    {
-        let z = &[
+    ) -> Result<(), PolymathError> {
+        let sap_matrices = &pk.sap_matrices;
+        if instance_assignment.len() != sap_matrices.num_instance_variables() {
+            // the error counts the public inputs without the leading `1`
+            return Err(PolymathError::PublicInputLengthMismatch {
+                expected: sap_matrices.num_instance_variables().saturating_sub(1),
+                got: instance_assignment.len().saturating_sub(1),
+            });
+        }
+        if witness_assignment.len() != sap_matrices.num_r1cs_witness_variables() {
+            return Err(PolymathError::WitnessLengthMismatch {
+                expected: sap_matrices.num_r1cs_witness_variables(),
+                got: witness_assignment.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn witness_polynomials(
+        ppk: &PreparedProvingKey<'_, E>,
+        instance_assignment: &[F],
+        witness_assignment: &[F],
+    ) -> Result<WitnessPolynomials<E>, PolymathError> {
+        let pk = ppk.pk;
+        Self::check_assignment_lengths(pk, instance_assignment, witness_assignment)?;
+
+        let z = [
             instance_assignment,
             instance_assignment,
             witness_assignment,
             &Self::compute_y_vec(pk, instance_assignment, witness_assignment),
-        ];
-
-        let (num_constraints, num_columns) = pk.sap_matrices.size(); // (rows, columns) in U and W matrices
-        let domain = D::new(num_constraints).unwrap();
+        ]
+        .concat();
 
+        let domain = ppk.domain;
         let n = domain.size();
+        let m0 = instance_assignment.len();
 
-        let uj_poly_evals = Self::poly_evals(n, num_columns, |i, j| pk.sap_matrices.u(i, j));
-        let wj_poly_evals = Self::poly_evals(n, num_columns, |i, j| pk.sap_matrices.w(i, j));
-
-        let uj_x_zj_evals = Self::polynomials_mul_by_z_j(&uj_poly_evals, z);
-        let wj_x_zj_evals = Self::polynomials_mul_by_z_j(&wj_poly_evals, z);
-
-        let u_evals = Self::sum_vectors(&uj_x_zj_evals);
+        let (u_evals, witness_u_x_evals) = Self::sap_evals(n, pk.sap_matrices.u_rows(), &z, m0);
         let u_coeffs = Self::poly_coeffs(domain, u_evals);
-        let w_evals = Self::sum_vectors(&wj_x_zj_evals);
+        let (w_evals, witness_w_x_evals) = Self::sap_evals(n, pk.sap_matrices.w_rows(), &z, m0);
         let w_coeffs = Self::poly_coeffs(domain, w_evals);
 
-        let u2_coeffs = Self::square_polynomial(&u_coeffs)?;
+        let u2_coeffs = Self::square_polynomial(ppk.squaring_domain, &u_coeffs);
 
         let u_poly = DensePolynomial::from_coefficients_vec(u_coeffs);
         let u2_poly = DensePolynomial::from_coefficients_vec(u2_coeffs);
         let w_poly = DensePolynomial::from_coefficients_vec(w_coeffs);
 
         let h_numerator_poly = u2_poly + -w_poly;
-        let (h_poly, rem_poly) = h_numerator_poly.divide_by_vanishing_poly(domain).unwrap();
+        let (h_poly, rem_poly) = h_numerator_poly
+            .divide_by_vanishing_poly(domain)
+            .ok_or(SynthesisError::DivisionByZero)?;
 
-        assert!(!h_poly.is_zero() && h_poly.degree() <= n - 2);
-        assert!(rem_poly.is_zero());
+        // `U(X)² - W(X)` vanishes on the domain iff the SAP constraints are satisfied
+        if !rem_poly.is_zero() {
+            return Err(PolymathError::UnsatisfiedConstraints);
+        }
+        debug_assert!(!h_poly.is_zero() && h_poly.degree() <= n - 2);
+        debug_assert!(u_poly.degree() < n);
 
-        let r_a_poly = DensePolynomial::from_coefficients_vec(vec![F::rand(rng), F::rand(rng)]);
-        assert!(r_a_poly.degree() <= 1);
+        let u_g1 = Self::msm(&u_poly.coeffs, &pk.x_powers_g1);
+        let x_u_g1 = Self::msm(&u_poly.coeffs, &pk.x_powers_g1[1..]);
 
-        assert!(u_poly.degree() < n);
-        let a_g1 = Self::compute_a_g1(pk, &u_poly, &r_a_poly);
+        let h_zh_by_y_alpha_g1 = Self::msm(&h_poly.coeffs, &pk.x_powers_zh_by_y_alpha_g1);
 
-        let r_g1 = Self::compute_r_g1(pk, &u_poly, &r_a_poly);
+        let zj_mul_uj_wj_lcs_by_y_alpha_g1 = Self::msm(&z[m0..], &pk.uj_wj_lcs_by_y_alpha_g1);
 
-        let h_zh_by_y_alpha_g1 = Self::msm(&h_poly.coeffs, &pk.x_powers_zh_by_y_alpha_g1);
+        let c_without_r_a_g1 = zj_mul_uj_wj_lcs_by_y_alpha_g1 + h_zh_by_y_alpha_g1;
+
+        let witness_u_x_coeffs = Self::poly_coeffs(domain, witness_u_x_evals);
+        let witness_u_x_poly = DensePolynomial::from_coefficients_vec(witness_u_x_coeffs);
+
+        let witness_w_x_coeffs = Self::poly_coeffs(domain, witness_w_x_evals);
+        let witness_w_x_poly = DensePolynomial::from_coefficients_vec(witness_w_x_coeffs);
+
+        let witness_u_x_by_y_alpha_poly = Self::mul_by_x_power(
+            &SparsePolynomial::from(witness_u_x_poly),
+            (pk.vk.sigma * MINUS_ALPHA) as usize,
+        );
+        let witness_w_x_by_y_alpha_y_gamma_poly = Self::mul_by_x_power(
+            &SparsePolynomial::from(witness_w_x_poly),
+            (pk.vk.sigma * (MINUS_ALPHA + MINUS_GAMMA)) as usize,
+        );
+
+        let h_x_zh_x_by_y_alpha_y_gamma_poly = Self::mul_by_x_power(
+            &SparsePolynomial::from(h_numerator_poly),
+            (pk.vk.sigma * (MINUS_ALPHA + MINUS_GAMMA)) as usize,
+        );
+
+        let c_without_r_a_x_by_y_gamma_poly = witness_u_x_by_y_alpha_poly
+            + witness_w_x_by_y_alpha_y_gamma_poly
+            + h_x_zh_x_by_y_alpha_y_gamma_poly;
+
+        Ok(WitnessPolynomials {
+            instance_assignment: instance_assignment.to_vec(),
+            u_poly,
+            u_g1: u_g1.into(),
+            x_u_g1: x_u_g1.into(),
+            c_without_r_a_g1: c_without_r_a_g1.into(),
+            c_without_r_a_x_by_y_gamma_poly,
+        })
+    }
 
-        let zj_mul_uj_wj_lcs_by_y_alpha_g1 =
-            Self::msm(&z[1..].concat(), &pk.uj_wj_lcs_by_y_alpha_g1);
+    fn create_proof_with_witness_polynomials<R: RngCore>(
+        ppk: &PreparedProvingKey<'_, E>,
+        wp: &WitnessPolynomials<E>,
+        ctx: Option<&[u8]>,
+        rng: &mut R,
+    ) -> Result<Proof<E>, PolymathError> {
+        let pk = ppk.pk;
+        let n = ppk.domain.size();
+        let instance_assignment = &wp.instance_assignment;
+        let u_poly = &wp.u_poly;
+
+        let r_a_poly = DensePolynomial::from_coefficients_vec(vec![F::rand(rng), F::rand(rng)]);
+        debug_assert!(r_a_poly.degree() <= 1);
 
-        let c_g1 = zj_mul_uj_wj_lcs_by_y_alpha_g1 + h_zh_by_y_alpha_g1 + r_g1;
+        let a_g1 = Self::compute_a_g1(pk, wp, &r_a_poly);
 
-        let mut t = T::new(B_POLYMATH);
-        let x1 = Self::compute_x1(&mut t, instance_assignment, &[a_g1.into(), c_g1.into()])?;
+        let r_g1 = Self::compute_r_g1(pk, wp, &r_a_poly);
+
+        let c_g1 = wp.c_without_r_a_g1 + r_g1;
+
+        let mut t = Self::new_transcript(&pk.vk)?;
+        let x1 = Self::compute_x1(
+            &mut t,
+            ctx,
+            instance_assignment,
+            &[a_g1.into(), c_g1.into()],
+        )?;
 
         let y1 = Self::compute_y1(x1, pk.vk.sigma);
 
@@ -132,14 +399,21 @@ where
         let a_at_x1 = u_poly.evaluate(&x1) + r_a_poly.evaluate(&x1) * y1_alpha;
 
         let y1_gamma = Self::neg_power(y1, MINUS_GAMMA);
-        let pi_at_x1 = Self::compute_pi_at_x1(&pk.vk, instance_assignment, x1, y1_gamma);
+        let pi_at_x1 = Self::compute_pi_at_x1(
+            &pk.vk,
+            ppk.n_inv,
+            &ppk.omega_powers,
+            instance_assignment,
+            x1,
+            y1_gamma,
+        );
 
         // compute c_at_x1
         let c_at_x1 = Self::compute_c_at_x1(y1_gamma, y1_alpha, a_at_x1, pi_at_x1);
 
         // compute batch commitment
 
-        let u_poly = SparsePolynomial::from(u_poly);
+        let u_poly = SparsePolynomial::from(u_poly.clone());
         let r_a_poly = SparsePolynomial::from(r_a_poly);
 
         let u_x_by_y_gamma_poly =
@@ -153,36 +427,7 @@ where
 
         let r_x_by_y_gamma_poly = Self::compute_r_x_by_y_gamma_poly(pk, &u_poly, r_a_poly);
 
-        let m0 = instance_assignment.len();
-        let zj_uj_evals = &uj_x_zj_evals[m0..];
-        let zj_wj_evals = &wj_x_zj_evals[m0..];
-
-        let witness_u_x_evals = Self::sum_vectors(zj_uj_evals);
-        let witness_u_x_coeffs = Self::poly_coeffs(domain, witness_u_x_evals);
-        let witness_u_x_poly = DensePolynomial::from_coefficients_vec(witness_u_x_coeffs);
-
-        let witness_w_x_evals = Self::sum_vectors(zj_wj_evals);
-        let witness_w_x_coeffs = Self::poly_coeffs(domain, witness_w_x_evals);
-        let witness_w_x_poly = DensePolynomial::from_coefficients_vec(witness_w_x_coeffs);
-
-        let witness_u_x_by_y_alpha_poly = Self::mul_by_x_power(
-            &SparsePolynomial::from(witness_u_x_poly),
-            (pk.vk.sigma * MINUS_ALPHA) as usize,
-        );
-        let witness_w_x_by_y_alpha_y_gamma_poly = Self::mul_by_x_power(
-            &SparsePolynomial::from(witness_w_x_poly),
-            (pk.vk.sigma * (MINUS_ALPHA + MINUS_GAMMA)) as usize,
-        );
-
-        let h_x_zh_x_by_y_alpha_y_gamma_poly = Self::mul_by_x_power(
-            &SparsePolynomial::from(h_numerator_poly),
-            (pk.vk.sigma * (MINUS_ALPHA + MINUS_GAMMA)) as usize,
-        );
-
-        let c_x_by_y_gamma_poly = witness_u_x_by_y_alpha_poly
-            + witness_w_x_by_y_alpha_y_gamma_poly
-            + h_x_zh_x_by_y_alpha_y_gamma_poly
-            + r_x_by_y_gamma_poly;
+        let c_x_by_y_gamma_poly = wp.c_without_r_a_x_by_y_gamma_poly.clone() + r_x_by_y_gamma_poly;
 
         // compute H(X) = (A(X)·(Y^-𝛾) + x₂·C(X)·(Y^-𝛾)) - (A(x₁)·(Y^-𝛾) - x₂·C(x₁)·(Y^-𝛾))/(X - x₁)
 
@@ -217,9 +462,9 @@ where
         .divide_with_q_and_r(&DenseOrSparsePolynomial::from(
             DensePolynomial::from_coefficients_slice(&[-x1, F::one()]),
         ))
-        .unwrap();
-        assert!(rem_poly.is_zero());
-        assert!(
+        .ok_or(SynthesisError::DivisionByZero)?;
+        debug_assert!(rem_poly.is_zero());
+        debug_assert!(
             d_x_by_y_gamma_poly.degree()
                 <= 2 * (n - 1) + (pk.vk.sigma * (MINUS_ALPHA + MINUS_GAMMA)) as usize
         );
@@ -242,13 +487,28 @@ where
         result
     }
 
-    fn poly_evals<M>(n: usize, m: usize, m_ij: M) -> Vec<Vec<F>>
-    where
-        M: Fn(usize, usize) -> F + Sync,
-    {
-        cfg_into_iter!(0..m)
-            .map(|j| cfg_into_iter!(0..n).map(|i| m_ij(i, j)).collect())
-            .collect()
+    /// Evaluations `(∑ⱼ Mᵢⱼ·zⱼ)ᵢ` of the SAP matrix `M` given by its rows `m_rows`
+    /// over a domain of size `n`, together with their part `∑_{j ≥ m₀} Mᵢⱼ·zⱼ`
+    /// from the witness columns. Takes time proportional to the number of non-zero elements.
+    fn sap_evals(n: usize, m_rows: &CscMatrix<F>, z: &[F], m0: usize) -> (Vec<F>, Vec<F>) {
+        let (mut evals, mut witness_evals): (Vec<F>, Vec<F>) =
+            cfg_into_iter!(0..m_rows.num_columns())
+                .map(|i| {
+                    let mut instance_eval = F::zero();
+                    let mut witness_eval = F::zero();
+                    for (j, mij) in m_rows.column(i) {
+                        if j < m0 {
+                            instance_eval += mij * z[j];
+                        } else {
+                            witness_eval += mij * z[j];
+                        }
+                    }
+                    (instance_eval + witness_eval, witness_eval)
+                })
+                .unzip();
+        evals.resize(n, F::zero());
+        witness_evals.resize(n, F::zero());
+        (evals, witness_evals)
     }
 
     fn mul_by_x_power(poly: &SparsePolynomial<F>, power_of_x: usize) -> SparsePolynomial<F> {
@@ -257,65 +517,25 @@ where
         )
     }
 
-    fn sum_vectors(vs: &[Vec<F>]) -> Vec<F> {
-        vs.iter().fold(vec![F::zero(); vs[0].len()], |a, b| {
-            a.into_iter().zip(b).map(|(a, b)| a + b).collect()
-        })
-    }
+    /// `y = (0, ((1 - xᵢ)²)ᵢ, (((Aᵢ - Bᵢ)·z)²)ᵢ)` for the R1CS assignment `z = (x, w)`,
+    /// computed from the non-zero elements of the rows of `A` and `B`.
+    pub(crate) fn compute_y_vec(pk: &ProvingKey<E>, x: &[F], w: &[F]) -> Vec<F> {
+        let one = F::one();
+        let y_m0: Vec<F> = x[1..].iter().map(|&xi| (one - xi).square()).collect();
 
-    fn polynomials_mul_by_z_j(polynomials: &[Vec<F>], z: &[&[F]]) -> Vec<Vec<F>> {
-        polynomials
-            .iter()
-            .zip(0..)
-            .map(|(p_coeffs, j)| {
-                p_coeffs
-                    .iter()
-                    .map(move |&c| c * Self::combined_v_at(z, j))
-                    .collect()
-            })
-            .collect()
-    }
+        let z = |j: usize| if j < x.len() { x[j] } else { w[j - x.len()] };
+        let lc = |row: &[(F, usize)]| row.iter().map(|&(v, j)| v * z(j)).sum::<F>();
 
-    fn compute_y_vec(pk: &ProvingKey<E>, x: &[F], w: &[F]) -> Vec<F> {
-        let zero = F::zero();
-        let one = F::one();
-        let y_m0: Vec<F> = (1..pk.sap_matrices.num_instance_variables)
-            .map(|j| {
-                let v = one - x[j];
-                v * v
-            })
+        let (a, b) = (pk.sap_matrices.a(), pk.sap_matrices.b());
+        let y_n: Vec<F> = cfg_iter!(a)
+            .zip(b)
+            .map(|(a_i, b_i)| (lc(a_i) - lc(b_i)).square())
             .collect();
 
-        let (a, b) = (&pk.sap_matrices.a, &pk.sap_matrices.b);
-
-        let y_n: Vec<F> = (0..pk.sap_matrices.num_r1cs_constraints)
-            .map(|i| {
-                let num_r1cs_columns = pk.sap_matrices.num_instance_variables
-                    + pk.sap_matrices.num_r1cs_witness_variables;
-                let v = (0..num_r1cs_columns)
-                    .map(|j| (m_at(a, i, j) - m_at(b, i, j)) * Self::combined_v_at(&[x, w], j))
-                    .fold(zero, |x, y| x + y);
-                v * v
-            })
-            .collect();
         [vec![F::zero()], y_m0, y_n].concat()
     }
 
-    fn combined_v_at(vectors: &[&[F]], j: usize) -> F {
-        let mut j = j;
-        for &v in vectors {
-            if j < v.len() {
-                return v[j];
-            }
-            j -= v.len();
-        }
-        unreachable!()
-    }
-
-    fn square_polynomial(p_coeffs: &[F]) -> Result<Vec<F>, PolymathError> {
-        let squaring_domain: D<F> =
-            D::new(p_coeffs.len() * 2).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-
+    fn square_polynomial(squaring_domain: D<F>, p_coeffs: &[F]) -> Vec<F> {
         let mut u = squaring_domain.fft(p_coeffs); // u is a vector of evaluations
 
         for ui in &mut u {
@@ -324,29 +544,28 @@ where
 
         squaring_domain.ifft_in_place(&mut u); // u is now a coeffs vector
 
-        Ok(u)
+        u
     }
 
     fn compute_a_g1(
         pk: &ProvingKey<E>,
-        u_poly: &DensePolynomial<F>,
+        wp: &WitnessPolynomials<E>,
         r_a_poly: &DensePolynomial<F>,
     ) -> E::G1 {
-        let u_g1 = Self::msm(&u_poly.coeffs, &pk.x_powers_g1);
         let r_a_y_alpha_g1 = Self::msm(&r_a_poly.coeffs, &pk.x_powers_y_alpha_g1);
-        u_g1 + r_a_y_alpha_g1
+        r_a_y_alpha_g1 + wp.u_g1
     }
 
     fn compute_r_g1(
         pk: &ProvingKey<E>,
-        u_poly: &DensePolynomial<F>,
+        wp: &WitnessPolynomials<E>,
         r_a_poly: &DensePolynomial<F>,
     ) -> E::G1 {
         let two = F::one() + F::one();
 
-        // r_a is degree 1, so naive mul is cheaper than via FFTs
-        let two_r_a_by_u_poly = u_poly.naive_mul(r_a_poly).mul(two);
-        let two_r_a_by_u_g1 = Self::msm(&two_r_a_by_u_poly.coeffs, &pk.x_powers_g1);
+        // r_a is degree 1: [r_a(x)·U(x)]₁ = r_a₀·[U(x)]₁ + r_a₁·[x·U(x)]₁
+        let r_a_coeff = |i: usize| r_a_poly.coeffs.get(i).copied().unwrap_or_default();
+        let two_r_a_by_u_g1 = (wp.u_g1.mul(r_a_coeff(0)) + wp.x_u_g1.mul(r_a_coeff(1))).mul(two);
 
         let r_a_square_poly = r_a_poly.naive_mul(r_a_poly);
         let r_a_square_y_alpha_g1 = Self::msm(&r_a_square_poly.coeffs, &pk.x_powers_y_alpha_g1);
@@ -376,10 +595,13 @@ where
         two_r_a_x_u_by_y_gamma_poly + r_a_square_by_y_gamma_minus_alpha_poly + r_a_poly
     }
 
+    /// The key vector lengths are checked by [`ProvingKey::check_shape`]
+    /// when the key is prepared, and the lengths of caller-provided witness polynomials
+    /// by [`Polymath::check_witness_polynomials`]: `scalars` is never longer than `g1_elems`.
     #[inline]
-    fn msm(scalars: &Vec<F>, g1_elems: &Vec<E::G1Affine>) -> E::G1 {
-        assert!(scalars.len() <= g1_elems.len());
+    fn msm(scalars: &[F], g1_elems: &[E::G1Affine]) -> E::G1 {
+        debug_assert!(scalars.len() <= g1_elems.len());
 
-        E::G1::msm_unchecked(g1_elems.as_slice(), scalars.as_slice())
+        E::G1::msm_unchecked(g1_elems, scalars)
     }
 }
@@ -0,0 +1,396 @@
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::{format, string::String, vec::Vec};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    keccak256::Keccak256Transcript, FiatShamirVersion, Polymath, PolymathError, Proof, VerifyingKey,
+};
+
+/// Signature of the verification function of the generated contract.
+const VERIFY_PROOF_SIGNATURE: &str = "verifyProof(uint256[7],uint256[])";
+
+impl<V: FiatShamirVersion> Polymath<Bn254, Keccak256Transcript<Fr>, V> {
+    /// Generate the source of a self-contained Solidity contract `PolymathVerifier`
+    /// that verifies proofs against `vk`.
+    ///
+    /// The contract exposes `verifyProof(uint256[7] proof, uint256[] input) returns (bool)`,
+    /// see [`Self::solidity_calldata`] for the encoding of the arguments.
+    /// It recomputes `x1` and `x2` with the same `keccak256` transcript as
+    /// [`Keccak256Transcript`] under the layout `V`, which absorbs the field elements and
+    /// the points as the same big-endian words as the calldata, and checks the pairing
+    /// equation with the `0x08` precompile.
+    ///
+    /// Proofs created with a context (see [`Polymath::prove_with_context`]) don't verify
+    /// with the generated contract.
+    pub fn solidity_verifier(vk: &VerifyingKey<Bn254>) -> Result<String, PolymathError> {
+        // the contract starts from the same transcript state as the native verifier
+        let transcript_prefix = Self::new_transcript(vk)?.transcript;
+        let n_inv = Fr::from(vk.n).inverse().unwrap();
+
+        let mut constants = Vec::new();
+        constants.push(format!(
+            "uint256 internal constant R = {};",
+            word(Fr::MODULUS)
+        ));
+        constants.push(format!(
+            "uint256 internal constant Q = {};",
+            word(Fq::MODULUS)
+        ));
+        constants.push(String::new());
+        constants.push(format!("uint256 internal constant N = {};", vk.n));
+        constants.push(format!("uint256 internal constant M0 = {};", vk.m0));
+        constants.push(format!("uint256 internal constant SIGMA = {};", vk.sigma));
+        constants.push(format!(
+            "uint256 internal constant OMEGA = {};",
+            word(vk.omega.into_bigint())
+        ));
+        constants.push(format!(
+            "uint256 internal constant N_INV = {};",
+            word(n_inv.into_bigint())
+        ));
+        constants.push(String::new());
+        constants.extend(g1_constants("ONE_G1", &vk.e.one_g1));
+        constants.extend(g2_constants("ONE_G2", &vk.e.one_g2));
+        constants.extend(g2_constants("X_G2", &vk.e.x_g2));
+        constants.extend(g2_constants("Z_G2", &vk.e.z_g2));
+        constants.push(String::new());
+        constants.push(format!(
+            "bytes internal constant TRANSCRIPT_PREFIX = hex\"{}\";",
+            hex(&transcript_prefix)
+        ));
+
+        let mut source = String::from(CONTRACT_HEADER);
+        for constant in constants {
+            if !constant.is_empty() {
+                source.push_str("    ");
+                source.push_str(&constant);
+            }
+            source.push('\n');
+        }
+        source.push_str(CONTRACT_BODY);
+        Ok(source)
+    }
+
+    /// Encode a call to `verifyProof` of the contract generated by [`Self::solidity_verifier`]
+    /// for `proof` and `public_inputs`.
+    ///
+    /// `proof` is passed as `[a]₁.x, [a]₁.y, [c]₁.x, [c]₁.y, A(x1), [d]₁.x, [d]₁.y`
+    /// and `public_inputs` as a dynamic array, both ABI-encoded as big-endian words.
+    /// The point at infinity is encoded as `(0, 0)`.
+    pub fn solidity_calldata(proof: &Proof<Bn254>, public_inputs: &[Fr]) -> Vec<u8> {
+        let selector = &Keccak256::digest(VERIFY_PROOF_SIGNATURE.as_bytes())[..4];
+
+        let mut words: Vec<Vec<u8>> = Vec::new();
+        words.extend(g1_words(&proof.a_g1));
+        words.extend(g1_words(&proof.c_g1));
+        words.push(proof.a_at_x1.into_bigint().to_bytes_be());
+        words.extend(g1_words(&proof.d_g1));
+        // offset of the dynamic array, counted from the start of the arguments
+        words.push(u64_word(8 * 32));
+        words.push(u64_word(public_inputs.len() as u64));
+        words.extend(public_inputs.iter().map(|x| x.into_bigint().to_bytes_be()));
+
+        let mut calldata = selector.to_vec();
+        for w in words {
+            calldata.extend(w);
+        }
+        calldata
+    }
+}
+
+fn g1_words(p: &G1Affine) -> [Vec<u8>; 2] {
+    let (x, y) = p.xy().unwrap_or_default();
+    [x.into_bigint().to_bytes_be(), y.into_bigint().to_bytes_be()]
+}
+
+fn g1_constants(name: &str, p: &G1Affine) -> [String; 2] {
+    let [x, y] = g1_words(p);
+    [
+        format!("uint256 internal constant {}_X = 0x{};", name, hex(&x)),
+        format!("uint256 internal constant {}_Y = 0x{};", name, hex(&y)),
+    ]
+}
+
+/// `Fq2` coordinates go in the order the pairing precompile expects:
+/// the imaginary part first.
+fn g2_constants(name: &str, p: &G2Affine) -> [String; 4] {
+    let (x, y) = p.xy().unwrap_or_default();
+    let limb = |suffix: &str, f: Fq| {
+        format!(
+            "uint256 internal constant {}_{} = {};",
+            name,
+            suffix,
+            word(f.into_bigint())
+        )
+    };
+    [
+        limb("X_IM", x.c1),
+        limb("X_RE", x.c0),
+        limb("Y_IM", y.c1),
+        limb("Y_RE", y.c0),
+    ]
+}
+
+fn u64_word(x: u64) -> Vec<u8> {
+    let mut w = vec![0u8; 24];
+    w.extend(x.to_be_bytes());
+    w
+}
+
+fn word<B: BigInteger>(x: B) -> String {
+    format!("0x{}", hex(&x.to_bytes_be()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+const CONTRACT_HEADER: &str = r#"// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.19;
+
+/// @title Polymath verifier
+/// @notice Verifies Polymath proofs over BN254 against a single verifying key.
+/// @dev Generated by charms-polymath. The Fiat-Shamir challenges are computed exactly as
+/// by `Keccak256Transcript`: each label and message is prefixed with its length as
+/// a big-endian u64, and the messages are the ABI-encoded uint256 words of the field elements
+/// and of the coordinates of the G1 points, lists starting with their length. A challenge is
+/// the first 48 bytes of keccak256(h | 0x00) | keccak256(h | 0x01), with h the hash of
+/// the transcript, reduced modulo r (see `wideChallenge`).
+contract PolymathVerifier {
+"#;
+
+const CONTRACT_BODY: &str = r#"
+    /// @notice Verify a proof for the public inputs `input`.
+    /// @param proof [a]_1.x, [a]_1.y, [c]_1.x, [c]_1.y, A(x1), [d]_1.x, [d]_1.y
+    /// @param input the public inputs, without the leading 1
+    function verifyProof(uint256[7] calldata proof, uint256[] calldata input)
+        public
+        view
+        returns (bool)
+    {
+        if (input.length + 1 != M0) return false;
+        for (uint256 i = 0; i < 7; i++) {
+            if (proof[i] >= (i == 4 ? R : Q)) return false;
+        }
+        for (uint256 i = 0; i < input.length; i++) {
+            if (input[i] >= R) return false;
+        }
+
+        (bytes32 state, uint256 x1) = challengeX1(proof, input);
+        uint256 cAtX1 = evaluateC(input, x1, proof[4]);
+        uint256 x2 = challengeX2(state, x1, proof[4], cAtX1);
+        return checkPairing(proof, x1, x2, cAtX1);
+    }
+
+    /// @dev x1 from keccak256(prefix | "public_inputs" | [1, input] | "commitments" | [a]_1, [c]_1 | "x1"),
+    /// with the labels and the messages prefixed with their length as a big-endian u64
+    function challengeX1(uint256[7] calldata proof, uint256[] calldata input)
+        internal
+        pure
+        returns (bytes32 state, uint256 x1)
+    {
+        state = keccak256(
+            abi.encodePacked(
+                TRANSCRIPT_PREFIX,
+                uint64(13),
+                "public_inputs",
+                uint64(32 + 32 * M0),
+                M0,
+                uint256(1),
+                input,
+                uint64(11),
+                "commitments",
+                uint64(32 + 4 * 32),
+                uint256(2),
+                proof[0],
+                proof[1],
+                proof[2],
+                proof[3],
+                uint64(2),
+                "x1"
+            )
+        );
+        x1 = wideChallenge(state);
+    }
+
+    /// @dev x2 from keccak256(state | "x1" | x1 | "values" | A(x1), C(x1) | "x2"),
+    /// with the labels and the messages prefixed with their length as a big-endian u64
+    function challengeX2(bytes32 state, uint256 x1, uint256 aAtX1, uint256 cAtX1)
+        internal
+        pure
+        returns (uint256)
+    {
+        bytes32 h = keccak256(
+            abi.encodePacked(
+                state,
+                uint64(2),
+                "x1",
+                uint64(32),
+                x1,
+                uint64(6),
+                "values",
+                uint64(32 + 2 * 32),
+                uint256(2),
+                aAtX1,
+                cAtX1,
+                uint64(2),
+                "x2"
+            )
+        );
+        return wideChallenge(h);
+    }
+
+    /// @dev The first 48 bytes of keccak256(h | 0x00) | keccak256(h | 0x01) as a big-endian
+    /// integer, reduced modulo R: hi * 2^128 + lo, with hi the first 32 bytes and lo the next 16.
+    function wideChallenge(bytes32 h) internal pure returns (uint256) {
+        uint256 hi = uint256(keccak256(abi.encodePacked(h, uint8(0))));
+        uint256 lo = uint256(keccak256(abi.encodePacked(h, uint8(1)))) >> 128;
+        return addmod(mulmod(hi, 1 << 128, R), lo, R);
+    }
+
+    /// @dev C(x1) = ((A(x1) + y1^gamma) * A(x1) - PI(x1)) / y1^alpha, with y1 = x1^sigma,
+    /// alpha = -3 and gamma = -5.
+    function evaluateC(uint256[] calldata input, uint256 x1, uint256 aAtX1)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256 y1 = expMod(x1, SIGMA);
+        uint256 y1Inv = expMod(y1, R - 2);
+        uint256 y1Gamma = mulmod(mulmod(y1Inv, y1Inv, R), y1Inv, R);
+        y1Gamma = mulmod(mulmod(y1Gamma, y1Inv, R), y1Inv, R);
+        uint256 y1Cube = mulmod(mulmod(y1, y1, R), y1, R);
+
+        uint256 pi = evaluatePi(input, x1, y1Gamma);
+        uint256 lhs = mulmod(addmod(aAtX1, y1Gamma, R), aAtX1, R);
+        return mulmod(addmod(lhs, R - pi, R), y1Cube, R);
+    }
+
+    /// @dev PI(x1) = sum_i z_i * w^i / (x1 - w^i) * (x1^n - 1) / n * y1^gamma for i in [0, 2 * m0),
+    /// with z_0 = 2, z_i = 1 + x_i for i < m0, z_m0 = 0 and z_i = 1 - x_(i - m0) otherwise.
+    /// The sum is accumulated as a single fraction to invert only once.
+    function evaluatePi(uint256[] calldata input, uint256 x1, uint256 y1Gamma)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256 num = 0;
+        uint256 den = 1;
+        uint256 w = 1;
+        for (uint256 i = 0; i < 2 * M0; i++) {
+            uint256 z;
+            if (i == 0) {
+                z = 2;
+            } else if (i < M0) {
+                z = addmod(1, input[i - 1], R);
+            } else if (i == M0) {
+                z = 0;
+            } else {
+                z = addmod(1, R - input[i - M0 - 1], R);
+            }
+            uint256 d = addmod(x1, R - w, R);
+            num = addmod(mulmod(num, d, R), mulmod(mulmod(z, w, R), den, R), R);
+            den = mulmod(den, d, R);
+            w = mulmod(w, OMEGA, R);
+        }
+        uint256 vanishing = addmod(expMod(x1, N), R - 1, R);
+        uint256 sum = mulmod(num, expMod(den, R - 2), R);
+        return mulmod(mulmod(sum, vanishing, R), mulmod(N_INV, y1Gamma, R), R);
+    }
+
+    /// @dev e(P, [z]_2) = e([d]_1, [x]_2) - e(x1 * [d]_1, [1]_2),
+    /// with P = [a]_1 + x2 * [c]_1 - (A(x1) + x2 * C(x1)) * [1]_1
+    function checkPairing(uint256[7] calldata proof, uint256 x1, uint256 x2, uint256 cAtX1)
+        internal
+        view
+        returns (bool)
+    {
+        uint256[18] memory input;
+        bool ok;
+        uint256 s = addmod(proof[4], mulmod(x2, cAtX1, R), R);
+        (ok, input[0], input[1]) = ecMul(ONE_G1_X, ONE_G1_Y, (R - s) % R);
+        if (!ok) return false;
+        (ok, input[2], input[3]) = ecMul(proof[2], proof[3], x2);
+        if (!ok) return false;
+        (ok, input[0], input[1]) = ecAdd(input[0], input[1], input[2], input[3]);
+        if (!ok) return false;
+        (ok, input[0], input[1]) = ecAdd(input[0], input[1], proof[0], proof[1]);
+        if (!ok) return false;
+        input[2] = Z_G2_X_IM;
+        input[3] = Z_G2_X_RE;
+        input[4] = Z_G2_Y_IM;
+        input[5] = Z_G2_Y_RE;
+
+        input[6] = proof[5];
+        input[7] = (Q - proof[6]) % Q;
+        input[8] = X_G2_X_IM;
+        input[9] = X_G2_X_RE;
+        input[10] = X_G2_Y_IM;
+        input[11] = X_G2_Y_RE;
+
+        (ok, input[12], input[13]) = ecMul(proof[5], proof[6], x1);
+        if (!ok) return false;
+        input[14] = ONE_G2_X_IM;
+        input[15] = ONE_G2_X_RE;
+        input[16] = ONE_G2_Y_IM;
+        input[17] = ONE_G2_Y_RE;
+
+        uint256[1] memory out;
+        assembly ("memory-safe") {
+            ok := staticcall(gas(), 0x08, input, 576, out, 0x20)
+        }
+        return ok && out[0] == 1;
+    }
+
+    function expMod(uint256 base, uint256 e) internal view returns (uint256 result) {
+        bool ok;
+        assembly ("memory-safe") {
+            let p := mload(0x40)
+            mstore(p, 0x20)
+            mstore(add(p, 0x20), 0x20)
+            mstore(add(p, 0x40), 0x20)
+            mstore(add(p, 0x60), base)
+            mstore(add(p, 0x80), e)
+            mstore(add(p, 0xa0), R)
+            ok := staticcall(gas(), 0x05, p, 0xc0, p, 0x20)
+            result := mload(p)
+        }
+        require(ok, "modexp failed");
+    }
+
+    function ecAdd(uint256 ax, uint256 ay, uint256 bx, uint256 by)
+        internal
+        view
+        returns (bool ok, uint256 x, uint256 y)
+    {
+        assembly ("memory-safe") {
+            let p := mload(0x40)
+            mstore(p, ax)
+            mstore(add(p, 0x20), ay)
+            mstore(add(p, 0x40), bx)
+            mstore(add(p, 0x60), by)
+            ok := staticcall(gas(), 0x06, p, 0x80, p, 0x40)
+            x := mload(p)
+            y := mload(add(p, 0x20))
+        }
+    }
+
+    function ecMul(uint256 ax, uint256 ay, uint256 s)
+        internal
+        view
+        returns (bool ok, uint256 x, uint256 y)
+    {
+        assembly ("memory-safe") {
+            let p := mload(0x40)
+            mstore(p, ax)
+            mstore(add(p, 0x20), ay)
+            mstore(add(p, 0x40), s)
+            ok := staticcall(gas(), 0x07, p, 0x60, p, 0x40)
+            x := mload(p)
+            y := mload(add(p, 0x20))
+        }
+    }
+}
+"#;
@@ -1,22 +1,84 @@
 use super::Transcript;
-use std::marker::PhantomData;
+use ark_std::marker::PhantomData;
 
-use ark_ec::pairing::Pairing;
-use ark_ff::{BigInteger, PrimeField};
+use ark_ff::PrimeField;
 use ark_std::vec::Vec;
 use blake3::Hasher;
 
-/// Transcript with `Blake3` hash function from sh3 crate.
+/// Transcript with `Blake3` hash function from blake3 crate.
+///
+/// The transcript is a byte string `T`, and a byte string `s` is absorbed as `len(s) ‖ s`,
+/// with `len(s)` as a big-endian `u64`:
+/// - `new(name)` starts with `T = name`;
+/// - `append_message(label, m)` appends `label` and `m`;
+/// - `challenge(label)` appends `label`, takes the `⌈(b + 128)/8⌉` first bytes of the
+///   extended output of `blake3(T)`, for fields of `b` bits, and reduces them as
+///   a big-endian integer modulo `p`. The transcript is then the first 32 bytes of this
+///   output, i.e. the regular hash `blake3(T)`.
+///
+/// The 128 extra bits keep the challenge statistically close to uniform.
 #[derive(Clone)]
 pub struct Blake3Transcript<F: PrimeField> {
     pub(crate) transcript: Vec<u8>,
     _f: PhantomData<F>,
 }
 
+impl<F: PrimeField> Blake3Transcript<F> {
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        self.transcript
+            .extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+        self.transcript.extend_from_slice(bytes);
+    }
+}
+
 impl<F: PrimeField> Transcript for Blake3Transcript<F> {
     type Challenge = F;
 
     fn new(name: &'static [u8]) -> Self {
+        let mut t = Self {
+            transcript: vec![],
+            _f: Default::default(),
+        };
+        t.append_bytes(name);
+        t
+    }
+
+    fn append_message<M: AsRef<[u8]>>(&mut self, label: &'static [u8], message: M) {
+        self.append_bytes(label);
+        self.append_bytes(message.as_ref());
+    }
+
+    fn challenge(&mut self, label: &'static [u8]) -> Self::Challenge {
+        self.append_bytes(label);
+        let len = (F::MODULUS_BIT_SIZE as usize + 128).div_ceil(8);
+        let mut buf = vec![0u8; len.max(32)];
+        Hasher::new()
+            .update(&self.transcript)
+            .finalize_xof()
+            .fill(&mut buf);
+        let challenge = F::from_be_bytes_mod_order(&buf[..len]);
+
+        buf.truncate(32);
+        self.transcript = buf;
+
+        challenge
+    }
+}
+
+/// [`Blake3Transcript`] before it absorbed the name and the lengths: labels and messages
+/// are concatenated as they are, so `("ab", "c")` and `("a", "bc")` give the same challenges.
+///
+/// Only use it to verify proofs created with this layout.
+#[derive(Clone)]
+pub struct LegacyBlake3Transcript<F: PrimeField> {
+    pub(crate) transcript: Vec<u8>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> Transcript for LegacyBlake3Transcript<F> {
+    type Challenge = F;
+
+    fn new(_name: &'static [u8]) -> Self {
         Self {
             transcript: vec![],
             _f: Default::default(),
@@ -1,22 +1,179 @@
 use super::Transcript;
-use std::marker::PhantomData;
+use ark_std::marker::PhantomData;
 
-use ark_ec::pairing::Pairing;
-use ark_ff::{BigInteger, PrimeField};
-use ark_std::vec::Vec;
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::SerializationError;
+use ark_std::{vec::Vec, Zero};
 use sha3::{Digest, Keccak256};
 
 /// Transcript with `keccak256` hash function from sh3 crate.
+///
+/// The transcript is a byte string `T`, and a byte string `s` is absorbed as `len(s) ‖ s`,
+/// with `len(s)` as a big-endian `u64`:
+/// - `new(name)` starts with `T = name`;
+/// - `append_message(label, m)` appends `label` and `m`;
+/// - `append_scalar`, `append_scalars`, `append_g1` and `append_points` append the label
+///   and the ABI encoding of the field elements or the points (see below) as `m`;
+/// - `challenge(label)` appends `label`, computes `h = keccak256(T)` and the
+///   `⌈(b + 128)/8⌉` first bytes of `keccak256(h ‖ 0x00) ‖ keccak256(h ‖ 0x01) ‖ …`
+///   (one byte counter), for fields of `b` bits, and reduces them as a big-endian integer
+///   modulo `p`. The transcript is then `T = h`.
+///
+/// The 128 extra bits keep the challenge statistically close to uniform.
+///
+/// Field elements and points are encoded as in the EVM ABI, so a Solidity verifier absorbs
+/// them with `abi.encodePacked` of their `uint256` words:
+/// - a field element is big-endian, left-padded to 32 bytes (or a multiple of 32 bytes
+///   for fields larger than 256 bits);
+/// - a point is the coordinates `x ‖ y` of its affine form, each as the elements of the base
+///   prime field, and zeros for the identity, as the `0x06` and `0x07` precompiles take G1
+///   points of BN254;
+/// - a list of field elements or points is its length as a 32-byte word, followed by
+///   the elements.
 #[derive(Clone)]
 pub struct Keccak256Transcript<F: PrimeField> {
     pub(crate) transcript: Vec<u8>,
     _f: PhantomData<F>,
 }
 
+impl<F: PrimeField> Keccak256Transcript<F> {
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        self.transcript
+            .extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+        self.transcript.extend_from_slice(bytes);
+    }
+}
+
+fn push_word(message: &mut Vec<u8>, n: u64) {
+    message.extend_from_slice(&[0u8; 24]);
+    message.extend_from_slice(&n.to_be_bytes());
+}
+
+fn push_field_element<S: PrimeField>(message: &mut Vec<u8>, s: &S) {
+    let bytes = s.into_bigint().to_bytes_be();
+    let len = (S::MODULUS_BIT_SIZE as usize).div_ceil(256) * 32;
+    let bytes = &bytes[bytes.len().saturating_sub(len)..];
+    message.resize(message.len() + len - bytes.len(), 0);
+    message.extend_from_slice(bytes);
+}
+
+fn push_point<G: AffineRepr>(message: &mut Vec<u8>, p: &G) {
+    let (x, y) = p.xy().unwrap_or((Zero::zero(), Zero::zero()));
+    for c in x
+        .to_base_prime_field_elements()
+        .chain(y.to_base_prime_field_elements())
+    {
+        push_field_element(message, &c);
+    }
+}
+
 impl<F: PrimeField> Transcript for Keccak256Transcript<F> {
     type Challenge = F;
 
     fn new(name: &'static [u8]) -> Self {
+        let mut t = Self {
+            transcript: vec![],
+            _f: Default::default(),
+        };
+        t.append_bytes(name);
+        t
+    }
+
+    fn append_message<M: AsRef<[u8]>>(&mut self, label: &'static [u8], message: M) {
+        self.append_bytes(label);
+        self.append_bytes(message.as_ref());
+    }
+
+    fn append_scalar<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalar: &S,
+    ) -> Result<(), SerializationError> {
+        let mut message = Vec::new();
+        push_field_element(&mut message, scalar);
+        self.append_message(label, message);
+        Ok(())
+    }
+
+    fn append_scalars<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalars: &[S],
+    ) -> Result<(), SerializationError> {
+        let mut message = Vec::new();
+        push_word(&mut message, scalars.len() as u64);
+        for s in scalars {
+            push_field_element(&mut message, s);
+        }
+        self.append_message(label, message);
+        Ok(())
+    }
+
+    fn append_g1<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        point: &G,
+    ) -> Result<(), SerializationError> {
+        let mut message = Vec::new();
+        push_point(&mut message, point);
+        self.append_message(label, message);
+        Ok(())
+    }
+
+    fn append_points<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        points: &[G],
+    ) -> Result<(), SerializationError> {
+        let mut message = Vec::new();
+        push_word(&mut message, points.len() as u64);
+        for p in points {
+            push_point(&mut message, p);
+        }
+        self.append_message(label, message);
+        Ok(())
+    }
+
+    fn challenge(&mut self, label: &'static [u8]) -> Self::Challenge {
+        self.append_bytes(label);
+        let h = Keccak256::digest(&self.transcript);
+
+        let len = (F::MODULUS_BIT_SIZE as usize + 128).div_ceil(8);
+        let mut buf = Vec::with_capacity(len + 32);
+        for counter in 0u8.. {
+            if buf.len() >= len {
+                break;
+            }
+            buf.extend_from_slice(
+                &Keccak256::new()
+                    .chain_update(h)
+                    .chain_update([counter])
+                    .finalize(),
+            );
+        }
+        let challenge = F::from_be_bytes_mod_order(&buf[..len]);
+
+        self.transcript = h.to_vec();
+
+        challenge
+    }
+}
+
+/// [`Keccak256Transcript`] before it absorbed the name and the lengths: labels and messages
+/// are concatenated as they are, so `("ab", "c")` and `("a", "bc")` give the same challenges.
+///
+/// Only use it to verify proofs created with this layout.
+#[derive(Clone)]
+pub struct LegacyKeccak256Transcript<F: PrimeField> {
+    pub(crate) transcript: Vec<u8>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> Transcript for LegacyKeccak256Transcript<F> {
+    type Challenge = F;
+
+    fn new(_name: &'static [u8]) -> Self {
         Self {
             transcript: vec![],
             _f: Default::default(),
@@ -1,6 +1,6 @@
 use crate::Transcript;
 use ark_ff::Field;
-use std::marker::PhantomData;
+use ark_std::marker::PhantomData;
 
 /// Transcript implementation producing field values as challenges using `merlin::Transcript`.
 #[derive(Clone)]
@@ -3,15 +3,23 @@
 pub mod blake3;
 pub mod keccak256;
 pub mod merlin;
+pub mod poseidon;
+pub mod sha256;
 
-use ark_ff::Field;
+use ark_ec::AffineRepr;
+use ark_ff::{Field, PrimeField};
+use ark_serialize::SerializationError;
 use ark_std::{convert::AsRef, marker::PhantomData};
 
+use crate::to_bytes;
+
 /// Transcript to produce Fiat-Shamir challenges.
 /// The transcript can be
 /// 1. a Merlin transcript, it's the default transcript
 /// 2. a Keccak256 transcript, which is Solidity-friendly transcript (instantiated with Keccak256 hash).
 /// 3. a Blake3 transcript (instantiated with blake3 hash).
+/// 4. a Poseidon transcript, which is cheap to reproduce in a circuit.
+/// 5. a SHA-256 transcript, with a byte layout for verifiers on Bitcoin.
 ///
 /// Feel free to use your own implementation instead of the above.
 pub trait Transcript: Send + Clone {
@@ -24,6 +32,90 @@ pub trait Transcript: Send + Clone {
     /// Append a message to the transcript.
     fn append_message<M: AsRef<[u8]>>(&mut self, label: &'static [u8], message: M);
 
+    /// Append a field element to the transcript.
+    ///
+    /// Defaults to appending its compressed serialization (see [`to_bytes!`]).
+    /// Transcripts over a field absorb the element natively instead.
+    fn append_scalar<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalar: &S,
+    ) -> Result<(), SerializationError> {
+        self.append_message(label, to_bytes!(scalar)?);
+        Ok(())
+    }
+
+    /// Append field elements to the transcript.
+    ///
+    /// Defaults to appending their compressed serialization (see [`to_bytes!`]),
+    /// which starts with their number.
+    fn append_scalars<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalars: &[S],
+    ) -> Result<(), SerializationError> {
+        self.append_message(label, to_bytes!(scalars)?);
+        Ok(())
+    }
+
+    /// Append a group element, e.g. a point of G1, to the transcript.
+    ///
+    /// Polymath itself only appends lists of points, with [`append_points`](Self::append_points);
+    /// this is for protocols built on the transcript that absorb single points.
+    ///
+    /// Defaults to appending a list of one point. Transcripts for external verifiers
+    /// encode a single point on its own, as its affine coordinates,
+    /// see [`Keccak256Transcript`](keccak256::Keccak256Transcript).
+    fn append_g1<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        point: &G,
+    ) -> Result<(), SerializationError> {
+        self.append_points(label, ark_std::slice::from_ref(point))
+    }
+
+    /// Append group elements to the transcript.
+    ///
+    /// Defaults to appending their compressed serialization (see [`to_bytes!`]).
+    /// Transcripts with a circuit counterpart absorb the coordinates instead,
+    /// which is much cheaper to reproduce in a circuit.
+    fn append_points<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        points: &[G],
+    ) -> Result<(), SerializationError> {
+        self.append_message(label, to_bytes!(points)?);
+        Ok(())
+    }
+
     /// Produce a challenge.
     fn challenge(&mut self, label: &'static [u8]) -> Self::Challenge;
 }
+
+/// Layout of the Fiat–Shamir transcript shared by the prover and the verifier.
+///
+/// Proofs only verify with the same version they were created with.
+pub trait FiatShamirVersion: Send + Sync + 'static {
+    /// Protocol version absorbed at the start of the transcript,
+    /// or `None` if the layout doesn't absorb a version.
+    const VERSION: Option<u64>;
+}
+
+/// Legacy transcript layout: only the public inputs and the proof elements are absorbed.
+///
+/// Nothing identifies the circuit or the key, so only use it to verify proofs created
+/// before [`FiatShamirV1`] was introduced.
+pub struct FiatShamirV0;
+
+impl FiatShamirVersion for FiatShamirV0 {
+    const VERSION: Option<u64> = None;
+}
+
+/// Transcript layout v1: the transcript starts with the protocol version
+/// and the [`VerifyingKey::digest`](crate::VerifyingKey::digest),
+/// so a proof is bound to the key it was created for.
+pub struct FiatShamirV1;
+
+impl FiatShamirVersion for FiatShamirV1 {
+    const VERSION: Option<u64> = Some(1);
+}
@@ -0,0 +1,339 @@
+//! Transcript with the Poseidon sponge, and its circuit counterpart.
+
+use super::Transcript;
+use ark_std::marker::PhantomData;
+
+use ark_crypto_primitives::sponge::{
+    poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::SerializationError;
+use ark_std::{convert::AsRef, vec::Vec, Zero};
+
+/// Full rounds of the Poseidon permutation.
+const FULL_ROUNDS: usize = 8;
+
+/// Partial rounds of the Poseidon permutation: 128-bit security for a state of 3 elements
+/// and `x⁵` S-boxes, with the margin recommended in the Poseidon paper.
+const PARTIAL_ROUNDS: usize = 57;
+
+/// Exponent of the S-boxes.
+const ALPHA: u64 = 5;
+
+/// Poseidon parameters of the transcripts over `CF`: rate 2, capacity 1, 8 full and 57 partial
+/// rounds and `x⁵` S-boxes, with the round constants and the MDS matrix from the Grain LFSR
+/// of the reference implementation.
+///
+/// Generating them takes a while, so with `std` they are computed once per field and cached.
+///
+/// Panics if `x⁵` is not a permutation of `CF`, i.e. if `5` divides `p - 1`.
+pub fn poseidon_config<CF: PrimeField>() -> PoseidonConfig<CF> {
+    #[cfg(feature = "std")]
+    {
+        use std::{
+            any::{Any, TypeId},
+            collections::BTreeMap,
+            sync::{Mutex, OnceLock, PoisonError},
+        };
+
+        type Configs = BTreeMap<TypeId, Box<dyn Any + Send + Sync>>;
+        static CONFIGS: OnceLock<Mutex<Configs>> = OnceLock::new();
+
+        let mut configs = CONFIGS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        configs
+            .entry(TypeId::of::<CF>())
+            .or_insert_with(|| Box::new(generate_poseidon_config::<CF>()))
+            .downcast_ref::<PoseidonConfig<CF>>()
+            .unwrap()
+            .clone()
+    }
+    #[cfg(not(feature = "std"))]
+    generate_poseidon_config()
+}
+
+fn generate_poseidon_config<CF: PrimeField>() -> PoseidonConfig<CF> {
+    let p_minus_one = (-CF::one()).into_bigint().to_bytes_be();
+    let rem = p_minus_one
+        .iter()
+        .fold(0u64, |rem, b| (rem * 256 + *b as u64) % ALPHA);
+    assert_ne!(rem, 0, "x^5 is not a permutation of the field");
+
+    let (ark, mds) = find_poseidon_ark_and_mds::<CF>(
+        CF::MODULUS_BIT_SIZE as u64,
+        2,
+        FULL_ROUNDS as u64,
+        PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, 2, 1)
+}
+
+/// Number of bits of an element of `S` packed into an element of `CF`:
+/// all of them if every element of `S` is smaller than the modulus of `CF`,
+/// otherwise as many as always fit.
+pub(crate) fn packed_bits<S: PrimeField, CF: PrimeField>() -> usize {
+    let max = (-S::one()).into_bigint().to_bits_le();
+    let fits = S::MODULUS_BIT_SIZE <= CF::MODULUS_BIT_SIZE
+        && CF::from_bigint(CF::BigInt::from_bits_le(
+            &max[..S::MODULUS_BIT_SIZE as usize],
+        ))
+        .is_some();
+    if fits {
+        S::MODULUS_BIT_SIZE as usize
+    } else {
+        CF::MODULUS_BIT_SIZE as usize - 1
+    }
+}
+
+/// `s` as elements of `CF`: its little-endian bits in chunks of [`packed_bits`].
+fn to_sponge_elements<S: PrimeField, CF: PrimeField>(s: &S) -> Vec<CF> {
+    let bits = s.into_bigint().to_bits_le();
+    bits[..S::MODULUS_BIT_SIZE as usize]
+        .chunks(packed_bits::<S, CF>())
+        .map(|chunk| CF::from_bigint(CF::BigInt::from_bits_le(chunk)).unwrap())
+        .collect()
+}
+
+/// Transcript with the Poseidon sponge over `CF`, producing challenges in `F`.
+///
+/// Field elements and points are absorbed as elements of `CF`, not as bytes, so the
+/// challenges are cheap to recompute in a circuit over `CF` with `PoseidonTranscriptVar`
+/// (`r1cs` feature). For the Polymath verifier gadget, `CF` is the base field of the curve;
+/// `CF = F` suits circuits over the scalar field.
+///
+/// Everything is absorbed as elements of `CF`:
+/// - a byte string as its length, followed by its bytes packed in little-endian chunks
+///   of `⌊(CF::MODULUS_BIT_SIZE - 1)/8⌋` bytes;
+/// - a label or a message as a byte string;
+/// - a field element as itself if all the elements of its field are smaller than
+///   the modulus of `CF`, otherwise as little-endian chunks of `CF::MODULUS_BIT_SIZE - 1` bits;
+/// - a list of field elements or points as its length, followed by the elements;
+/// - a point as the coordinates of its affine form (`(0, 0)` for the identity),
+///   followed by `1` for the identity and `0` otherwise.
+///
+/// The name of the transcript is absorbed first, and the label before every message
+/// and challenge. A challenge is the first `F::MODULUS_BIT_SIZE - 1` bits squeezed,
+/// little-endian, for every `F` and `CF`. See [`poseidon_config`] for the parameters.
+#[derive(Clone)]
+pub struct PoseidonTranscript<F: PrimeField, CF: PrimeField + Absorb = F> {
+    sponge: PoseidonSponge<CF>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField, CF: PrimeField + Absorb> PoseidonTranscript<F, CF> {
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        self.sponge.absorb(&CF::from(bytes.len() as u64));
+        self.sponge.absorb(&bytes);
+    }
+
+    fn absorb_point<G: AffineRepr>(&mut self, p: &G) {
+        let (x, y) = p.xy().unwrap_or((Zero::zero(), Zero::zero()));
+        let elements: Vec<CF> = x
+            .to_base_prime_field_elements()
+            .chain(y.to_base_prime_field_elements())
+            .flat_map(|c| to_sponge_elements(&c))
+            .chain([CF::from(p.is_zero())])
+            .collect();
+        self.sponge.absorb(&elements);
+    }
+}
+
+impl<F: PrimeField, CF: PrimeField + Absorb> Transcript for PoseidonTranscript<F, CF> {
+    type Challenge = F;
+
+    fn new(name: &'static [u8]) -> Self {
+        let mut t = Self {
+            sponge: PoseidonSponge::new(&poseidon_config()),
+            _f: PhantomData,
+        };
+        t.absorb_bytes(name);
+        t
+    }
+
+    fn append_message<M: AsRef<[u8]>>(&mut self, label: &'static [u8], message: M) {
+        self.absorb_bytes(label);
+        self.absorb_bytes(message.as_ref());
+    }
+
+    fn append_scalar<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalar: &S,
+    ) -> Result<(), SerializationError> {
+        self.absorb_bytes(label);
+        self.sponge.absorb(&to_sponge_elements::<S, CF>(scalar));
+        Ok(())
+    }
+
+    fn append_scalars<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalars: &[S],
+    ) -> Result<(), SerializationError> {
+        self.absorb_bytes(label);
+        self.sponge.absorb(&CF::from(scalars.len() as u64));
+        for s in scalars {
+            self.sponge.absorb(&to_sponge_elements::<S, CF>(s));
+        }
+        Ok(())
+    }
+
+    fn append_g1<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        point: &G,
+    ) -> Result<(), SerializationError> {
+        self.absorb_bytes(label);
+        self.absorb_point(point);
+        Ok(())
+    }
+
+    fn append_points<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        points: &[G],
+    ) -> Result<(), SerializationError> {
+        self.absorb_bytes(label);
+        self.sponge.absorb(&CF::from(points.len() as u64));
+        for p in points {
+            self.absorb_point(p);
+        }
+        Ok(())
+    }
+
+    fn challenge(&mut self, label: &'static [u8]) -> Self::Challenge {
+        self.absorb_bytes(label);
+        // `squeeze_field_elements` takes every bit when `F = CF`, but its circuit counterpart
+        // doesn't, so squeeze the bits the way `squeeze_emulated_field_elements` does.
+        let bits = self.sponge.squeeze_bits(F::MODULUS_BIT_SIZE as usize - 1);
+        F::from_bigint(F::BigInt::from_bits_le(&bits)).unwrap()
+    }
+}
+
+/// Constraints for [`PoseidonTranscript`].
+#[cfg(feature = "r1cs")]
+pub mod constraints {
+    use super::{packed_bits, poseidon_config, PoseidonTranscript};
+    use crate::constraints::TranscriptVar;
+
+    use ark_crypto_primitives::sponge::{
+        constraints::CryptographicSpongeVar, poseidon::constraints::PoseidonSpongeVar, Absorb,
+    };
+    use ark_ff::PrimeField;
+    use ark_r1cs_std::{
+        convert::ToConstraintFieldGadget,
+        fields::{emulated_fp::EmulatedFpVar, fp::FpVar},
+        prelude::*,
+    };
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use ark_std::{marker::PhantomData, vec::Vec};
+
+    /// Circuit counterpart of [`PoseidonTranscript`] over the constraint field `CF`.
+    ///
+    /// Points are converted with [`ToConstraintFieldGadget`], so they match the native
+    /// transcript for curves over `CF`, whose coordinates are native field elements.
+    pub struct PoseidonTranscriptVar<F: PrimeField, CF: PrimeField + Absorb> {
+        sponge: PoseidonSpongeVar<CF>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField, CF: PrimeField + Absorb> PoseidonTranscriptVar<F, CF> {
+        fn absorb_bytes(&mut self, bytes: &[UInt8<CF>]) -> Result<(), SynthesisError> {
+            self.sponge
+                .absorb(&FpVar::constant(CF::from(bytes.len() as u64)))?;
+            self.sponge.absorb(&bytes)
+        }
+
+        /// Same as `to_sponge_elements`, for a canonical element of `F`.
+        fn absorb_scalar(&mut self, scalar: &EmulatedFpVar<F, CF>) -> Result<(), SynthesisError> {
+            let bits = scalar.to_bits_le()?;
+            let elements = bits[..F::MODULUS_BIT_SIZE as usize]
+                .chunks(packed_bits::<F, CF>())
+                .map(Boolean::le_bits_to_fp)
+                .collect::<Result<Vec<_>, _>>()?;
+            self.sponge.absorb(&elements)
+        }
+    }
+
+    impl<F: PrimeField, CF: PrimeField + Absorb> TranscriptVar<F, CF> for PoseidonTranscriptVar<F, CF> {
+        type Native = PoseidonTranscript<F, CF>;
+
+        fn new(cs: ConstraintSystemRef<CF>, name: &'static [u8]) -> Result<Self, SynthesisError> {
+            let mut t = Self {
+                sponge: PoseidonSpongeVar::new(cs, &poseidon_config()),
+                _f: PhantomData,
+            };
+            t.absorb_bytes(&UInt8::constant_vec(name))?;
+            Ok(t)
+        }
+
+        fn append_message(
+            &mut self,
+            label: &'static [u8],
+            message: &[UInt8<CF>],
+        ) -> Result<(), SynthesisError> {
+            self.absorb_bytes(&UInt8::constant_vec(label))?;
+            self.absorb_bytes(message)
+        }
+
+        fn append_scalar(
+            &mut self,
+            label: &'static [u8],
+            scalar: &EmulatedFpVar<F, CF>,
+        ) -> Result<(), SynthesisError> {
+            self.absorb_bytes(&UInt8::constant_vec(label))?;
+            self.absorb_scalar(scalar)
+        }
+
+        fn append_scalars(
+            &mut self,
+            label: &'static [u8],
+            scalars: &[EmulatedFpVar<F, CF>],
+        ) -> Result<(), SynthesisError> {
+            self.absorb_bytes(&UInt8::constant_vec(label))?;
+            self.sponge
+                .absorb(&FpVar::constant(CF::from(scalars.len() as u64)))?;
+            for s in scalars {
+                self.absorb_scalar(s)?;
+            }
+            Ok(())
+        }
+
+        fn append_g1<G: ToConstraintFieldGadget<CF>>(
+            &mut self,
+            label: &'static [u8],
+            point: &G,
+        ) -> Result<(), SynthesisError> {
+            self.absorb_bytes(&UInt8::constant_vec(label))?;
+            self.sponge.absorb(&point.to_constraint_field()?)
+        }
+
+        fn append_points<G: ToConstraintFieldGadget<CF>>(
+            &mut self,
+            label: &'static [u8],
+            points: &[G],
+        ) -> Result<(), SynthesisError> {
+            self.absorb_bytes(&UInt8::constant_vec(label))?;
+            self.sponge
+                .absorb(&FpVar::constant(CF::from(points.len() as u64)))?;
+            for p in points {
+                self.sponge.absorb(&p.to_constraint_field()?)?;
+            }
+            Ok(())
+        }
+
+        fn challenge(
+            &mut self,
+            label: &'static [u8],
+        ) -> Result<EmulatedFpVar<F, CF>, SynthesisError> {
+            self.absorb_bytes(&UInt8::constant_vec(label))?;
+            let (challenges, _) = self.sponge.squeeze_emulated_field_elements(1)?;
+            Ok(challenges[0].clone())
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Transcript with the SHA-256 hash function, for verifiers on Bitcoin.
+
+use super::Transcript;
+use ark_std::marker::PhantomData;
+
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::SerializationError;
+use ark_std::{convert::AsRef, vec::Vec, Zero};
+use sha2::{Digest, Sha256};
+
+/// Transcript with the `SHA-256` hash function from sha2 crate, with a byte layout simple
+/// enough to recompute the challenges in Bitcoin script or a BitVM verifier.
+///
+/// Integers and field elements are big-endian:
+/// - a byte string `s` is `len(s) ‖ s`, with `len(s)` on 8 bytes;
+/// - a field element is `⌈b/8⌉` bytes, for fields of `b` bits;
+/// - a point is the coordinates `x ‖ y` of its affine form, each as the elements of
+///   the base prime field, and zeros for the identity;
+/// - a list of field elements or points is its length on 8 bytes, followed by the elements.
+///
+/// The transcript is a byte string `T`:
+/// - `new(name)` starts with `T = name` (as a byte string);
+/// - `append_message(label, m)` appends `label ‖ m` (both as byte strings),
+///   `append_scalar`, `append_scalars`, `append_g1` and `append_points` the label followed
+///   by the field elements or the points;
+/// - `challenge(label)` appends `label`, computes `h = SHA-256(T)` and the
+///   `⌈(b + 128)/8⌉` first bytes of `SHA-256(h ‖ 0x00) ‖ SHA-256(h ‖ 0x01) ‖ …`
+///   (one byte counter), and reduces them modulo `p`. The transcript is then `T = h`.
+#[derive(Clone)]
+pub struct Sha256Transcript<F: PrimeField> {
+    pub(crate) transcript: Vec<u8>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> Sha256Transcript<F> {
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        self.transcript
+            .extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+        self.transcript.extend_from_slice(bytes);
+    }
+
+    fn append_field_element<S: PrimeField>(&mut self, s: &S) {
+        let bytes = s.into_bigint().to_bytes_be();
+        let len = (S::MODULUS_BIT_SIZE as usize).div_ceil(8);
+        self.transcript
+            .extend_from_slice(&bytes[bytes.len() - len..]);
+    }
+
+    fn append_point<G: AffineRepr>(&mut self, p: &G) {
+        let (x, y) = p.xy().unwrap_or((Zero::zero(), Zero::zero()));
+        for c in x
+            .to_base_prime_field_elements()
+            .chain(y.to_base_prime_field_elements())
+        {
+            self.append_field_element(&c);
+        }
+    }
+}
+
+impl<F: PrimeField> Transcript for Sha256Transcript<F> {
+    type Challenge = F;
+
+    fn new(name: &'static [u8]) -> Self {
+        let mut t = Self {
+            transcript: vec![],
+            _f: Default::default(),
+        };
+        t.append_bytes(name);
+        t
+    }
+
+    fn append_message<M: AsRef<[u8]>>(&mut self, label: &'static [u8], message: M) {
+        self.append_bytes(label);
+        self.append_bytes(message.as_ref());
+    }
+
+    fn append_scalar<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalar: &S,
+    ) -> Result<(), SerializationError> {
+        self.append_bytes(label);
+        self.append_field_element(scalar);
+        Ok(())
+    }
+
+    fn append_scalars<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        scalars: &[S],
+    ) -> Result<(), SerializationError> {
+        self.append_bytes(label);
+        self.transcript
+            .extend_from_slice(&(scalars.len() as u64).to_be_bytes());
+        for s in scalars {
+            self.append_field_element(s);
+        }
+        Ok(())
+    }
+
+    fn append_g1<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        point: &G,
+    ) -> Result<(), SerializationError> {
+        self.append_bytes(label);
+        self.append_point(point);
+        Ok(())
+    }
+
+    fn append_points<G: AffineRepr>(
+        &mut self,
+        label: &'static [u8],
+        points: &[G],
+    ) -> Result<(), SerializationError> {
+        self.append_bytes(label);
+        self.transcript
+            .extend_from_slice(&(points.len() as u64).to_be_bytes());
+        for p in points {
+            self.append_point(p);
+        }
+        Ok(())
+    }
+
+    fn challenge(&mut self, label: &'static [u8]) -> Self::Challenge {
+        self.append_bytes(label);
+        let h = Sha256::digest(&self.transcript);
+
+        let len = (F::MODULUS_BIT_SIZE as usize + 128).div_ceil(8);
+        let mut buf = Vec::with_capacity(len + 32);
+        let mut counter = 0u8;
+        while buf.len() < len {
+            buf.extend_from_slice(
+                &Sha256::new()
+                    .chain_update(h)
+                    .chain_update([counter])
+                    .finalize(),
+            );
+            counter += 1;
+        }
+        let challenge = F::from_be_bytes_mod_order(&buf[..len]);
+
+        self.transcript = h.to_vec();
+
+        challenge
+    }
+}
@@ -0,0 +1,420 @@
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+#[cfg(feature = "prover")]
+use ark_ff::PrimeField;
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+#[cfg(feature = "prover")]
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::Valid;
+use ark_std::{rand::RngCore, vec::Vec};
+
+#[cfg(feature = "prover")]
+use crate::{
+    common::CscMatrix, FiatShamirVersion, Polymath, SapRow, Transcript, UnsatisfiedConstraint,
+};
+use crate::{
+    common::{BND_A, MINUS_ALPHA, MINUS_GAMMA},
+    PolymathError, Proof, ProvingKey,
+};
+
+type D<F> = Radix2EvaluationDomain<F>;
+
+/// `G2` element on the right-hand side of a [`Relation`].
+#[derive(Clone, Copy)]
+enum Base {
+    /// `[x]₂`
+    X,
+    /// `[z]₂`
+    Z,
+}
+
+/// Pairing relation `e(lhsₖ, [1]₂) = e(rhsₖ, base)` for every `k`.
+struct Relation<E: Pairing> {
+    name: &'static str,
+    lhs: Vec<E::G1>,
+    rhs: Vec<E::G1>,
+    base: Base,
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Check that `[a]₁`, `[c]₁` and `[d]₁` are points in the prime-order subgroup
+    /// and none of them is the identity.
+    pub fn validate(&self) -> Result<(), PolymathError> {
+        for (element, point) in [
+            ("a_g1", &self.a_g1),
+            ("c_g1", &self.c_g1),
+            ("d_g1", &self.d_g1),
+        ] {
+            if point.is_zero() {
+                return Err(PolymathError::InvalidProofElement {
+                    element,
+                    reason: "point at infinity",
+                });
+            }
+            if point.check().is_err() {
+                return Err(PolymathError::InvalidProofElement {
+                    element,
+                    reason: "not in the prime-order subgroup",
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Check that the proving key is well-formed and consistent with its verifying key `vk`:
+    /// the key parameters match the SAP matrices, vectors have the expected lengths,
+    /// and every group element is the one the trapdoors `x` and `z` in `vk` determine.
+    ///
+    /// Each element is checked with its own pairing equation, so the returned error names
+    /// the failed check. See [`ProvingKey::validate_batched`] for a faster randomized version.
+    pub fn validate(&self) -> Result<(), PolymathError> {
+        for relation in self.relations()? {
+            let base = self.base_g2(relation.base);
+            for (lhs, rhs) in relation.lhs.iter().zip(&relation.rhs) {
+                let pairing_output = E::multi_pairing(
+                    [lhs.into_affine(), (-*rhs).into_affine()],
+                    [self.vk.e.one_g2, base],
+                );
+                if !pairing_output.0.is_one() {
+                    return Err(PolymathError::InvalidKey(relation.name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Same checks as [`ProvingKey::validate`], but all pairing equations are combined
+    /// with random coefficients into a single multi-pairing.
+    ///
+    /// A failure doesn't tell which element is wrong: run [`ProvingKey::validate`] for that.
+    pub fn validate_batched<R: RngCore>(&self, rng: &mut R) -> Result<(), PolymathError> {
+        let mut lhs = E::G1::zero();
+        let mut rhs_x = E::G1::zero();
+        let mut rhs_z = E::G1::zero();
+
+        for relation in self.relations()? {
+            let rho: Vec<E::ScalarField> = (0..relation.lhs.len())
+                .map(|_| E::ScalarField::rand(rng))
+                .collect();
+
+            lhs += Self::combine(&relation.lhs, &rho);
+            let rhs = Self::combine(&relation.rhs, &rho);
+            match relation.base {
+                Base::X => rhs_x += rhs,
+                Base::Z => rhs_z += rhs,
+            }
+        }
+
+        let pairing_output = E::multi_pairing(
+            [
+                lhs.into_affine(),
+                (-rhs_x).into_affine(),
+                (-rhs_z).into_affine(),
+            ],
+            [self.vk.e.one_g2, self.vk.e.x_g2, self.vk.e.z_g2],
+        );
+        if !pairing_output.0.is_one() {
+            return Err(PolymathError::InvalidKey("batched pairing check failed"));
+        }
+        Ok(())
+    }
+
+    fn base_g2(&self, base: Base) -> E::G2Affine {
+        match base {
+            Base::X => self.vk.e.x_g2,
+            Base::Z => self.vk.e.z_g2,
+        }
+    }
+
+    fn combine(elems: &[E::G1], rho: &[E::ScalarField]) -> E::G1 {
+        E::G1::msm_unchecked(&E::G1::normalize_batch(elems), rho)
+    }
+
+    /// Check that the key parameters match the SAP matrices and the key vectors have
+    /// the lengths they imply, and return the evaluation domain of the SAP polynomials.
+    ///
+    /// This is all the prover relies on to not go out of bounds.
+    pub(crate) fn check_shape(&self) -> Result<D<E::ScalarField>, PolymathError> {
+        let vk = &self.vk;
+        let (num_constraints, num_columns) = self.sap_matrices.size();
+        let domain =
+            D::<E::ScalarField>::new(num_constraints).ok_or(PolymathError::DomainTooLarge {
+                size: num_constraints,
+            })?;
+
+        let n = domain.size();
+        let m0 = self.sap_matrices.num_instance_variables();
+        let sigma = n + 3;
+
+        if vk.n != n as u64 {
+            return Err(PolymathError::InvalidKey(
+                "n doesn't match the SAP matrices",
+            ));
+        }
+        if vk.m0 != m0 as u64 {
+            return Err(PolymathError::InvalidKey(
+                "m0 doesn't match the SAP matrices",
+            ));
+        }
+        if vk.sigma != sigma as u64 {
+            return Err(PolymathError::InvalidKey("sigma is not n + 3"));
+        }
+        if vk.omega != domain.group_gen() {
+            return Err(PolymathError::InvalidKey(
+                "omega is not the domain generator",
+            ));
+        }
+        if vk.e.one_g1.is_zero()
+            || vk.e.one_g2.is_zero()
+            || vk.e.x_g2.is_zero()
+            || vk.e.z_g2.is_zero()
+        {
+            return Err(PolymathError::InvalidKey(
+                "pairing VK contains the identity",
+            ));
+        }
+
+        let minus_alpha = MINUS_ALPHA as usize;
+        let minus_gamma = MINUS_GAMMA as usize;
+
+        Self::check_length("x_powers_g1", &self.x_powers_g1, n + BND_A)?;
+        Self::check_length(
+            "x_powers_y_alpha_g1",
+            &self.x_powers_y_alpha_g1,
+            2 * BND_A + 1,
+        )?;
+        Self::check_length("x_powers_y_gamma_g1", &self.x_powers_y_gamma_g1, BND_A + 1)?;
+        Self::check_length(
+            "x_powers_y_gamma_z_g1",
+            &self.x_powers_y_gamma_z_g1,
+            2 * (n - 1) + sigma * (minus_alpha + minus_gamma) + 1,
+        )?;
+        Self::check_length(
+            "x_powers_zh_by_y_alpha_g1",
+            &self.x_powers_zh_by_y_alpha_g1,
+            n - 1,
+        )?;
+        Self::check_length(
+            "uj_wj_lcs_by_y_alpha_g1",
+            &self.uj_wj_lcs_by_y_alpha_g1,
+            num_columns - m0,
+        )?;
+
+        if vk.e.one_g1 != self.x_powers_g1[0] {
+            return Err(PolymathError::InvalidKey("one_g1 is not x_powers_g1[0]"));
+        }
+
+        Ok(domain)
+    }
+
+    /// Check the key shape with [`ProvingKey::check_shape`], and collect the pairing relations
+    /// the key elements must satisfy.
+    ///
+    /// With `s = -𝛾·𝜎`, `[(xⁱ·y^𝛾·z)ᵢ]₁` is the sequence `[x^(k-s)·z]₁` covering every power
+    /// of `x` the other vectors use, so each of them is tied to it with a pairing against `[z]₂`.
+    fn relations(&self) -> Result<Vec<Relation<E>>, PolymathError> {
+        let domain = self.check_shape()?;
+        let (_, num_columns) = self.sap_matrices.size();
+
+        let n = domain.size();
+        let m0 = self.sap_matrices.num_instance_variables();
+        let sigma = n + 3;
+        let minus_alpha = MINUS_ALPHA as usize;
+        let minus_gamma = MINUS_GAMMA as usize;
+        let s = sigma * minus_gamma;
+
+        let projective =
+            |v: &[E::G1Affine]| -> Vec<E::G1> { v.iter().map(|&p| p.into()).collect() };
+        let gz = projective(&self.x_powers_y_gamma_z_g1);
+
+        let mut relations = vec![];
+
+        // consecutive elements differ by a factor of `x`
+        for (name, v) in [
+            (
+                "x_powers_g1 is not a sequence of powers of x",
+                &self.x_powers_g1,
+            ),
+            (
+                "x_powers_y_alpha_g1 is not a sequence of powers of x",
+                &self.x_powers_y_alpha_g1,
+            ),
+            (
+                "x_powers_y_gamma_g1 is not a sequence of powers of x",
+                &self.x_powers_y_gamma_g1,
+            ),
+            (
+                "x_powers_y_gamma_z_g1 is not a sequence of powers of x",
+                &self.x_powers_y_gamma_z_g1,
+            ),
+        ] {
+            relations.push(Relation {
+                name,
+                lhs: projective(&v[1..]),
+                rhs: projective(&v[..v.len() - 1]),
+                base: Base::X,
+            });
+        }
+
+        // `[xᵏ·y^𝛾·z]₁` for `k = 0`, `k = (𝛼-𝛾)·𝜎` and `k = -𝛾·𝜎` is `[y^𝛾·z]₁`, `[y^𝛼·z]₁` and `[z]₁`
+        relations.push(Relation {
+            name: "x_powers_y_gamma_g1 doesn't match x_powers_y_gamma_z_g1",
+            lhs: vec![gz[0]],
+            rhs: vec![self.x_powers_y_gamma_g1[0].into()],
+            base: Base::Z,
+        });
+        relations.push(Relation {
+            name: "x_powers_y_alpha_g1 doesn't match x_powers_y_gamma_z_g1",
+            lhs: vec![gz[sigma * (minus_gamma - minus_alpha)]],
+            rhs: vec![self.x_powers_y_alpha_g1[0].into()],
+            base: Base::Z,
+        });
+        relations.push(Relation {
+            name: "x_powers_g1 doesn't match x_powers_y_gamma_z_g1",
+            lhs: vec![gz[s]],
+            rhs: vec![self.x_powers_g1[0].into()],
+            base: Base::Z,
+        });
+
+        // `xⁱ·Z_H(x)/y^𝛼 = x^(i+n-𝛼·𝜎) - x^(i-𝛼·𝜎)`
+        let zh_offset = s + sigma * minus_alpha;
+        relations.push(Relation {
+            name: "x_powers_zh_by_y_alpha_g1 doesn't match x_powers_y_gamma_z_g1",
+            lhs: (0..self.x_powers_zh_by_y_alpha_g1.len())
+                .map(|i| gz[zh_offset + i + n] - gz[zh_offset + i])
+                .collect(),
+            rhs: projective(&self.x_powers_zh_by_y_alpha_g1),
+            base: Base::Z,
+        });
+
+        // `(uⱼ(x)·y^𝛾 + wⱼ(x))/y^𝛼 = ∑ᵢ (Uᵢⱼ·Lᵢ(x)·y^(𝛾-𝛼) + Wᵢⱼ·Lᵢ(x)/y^𝛼)`,
+        // `[Lᵢ(x)·xᵏ·z]₁` is the inverse FFT of `[(x^(k+l)·z)ₗ]₁`
+        let l_by_y_gamma_alpha_z = domain.ifft(&gz[s - sigma * (minus_gamma - minus_alpha)..][..n]);
+        let l_by_y_alpha_z = domain.ifft(&gz[zh_offset..][..n]);
+        let (u, w) = (self.sap_matrices.u_csc(), self.sap_matrices.w_csc());
+        relations.push(Relation {
+            name: "uj_wj_lcs_by_y_alpha_g1 doesn't match the SAP matrices",
+            lhs: (m0..num_columns)
+                .map(|j| {
+                    let uj = u.column(j).map(|(i, uij)| l_by_y_gamma_alpha_z[i] * uij);
+                    let wj = w.column(j).map(|(i, wij)| l_by_y_alpha_z[i] * wij);
+                    uj.chain(wj).sum()
+                })
+                .collect(),
+            rhs: projective(&self.uj_wj_lcs_by_y_alpha_g1),
+            base: Base::Z,
+        });
+
+        Ok(relations)
+    }
+
+    fn check_length<T>(name: &'static str, v: &[T], expected: usize) -> Result<(), PolymathError> {
+        if v.len() != expected {
+            return Err(PolymathError::InvalidKeyLength {
+                name,
+                expected,
+                got: v.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// built on the witness computation of the prover
+#[cfg(feature = "prover")]
+impl<F: PrimeField, E, T, V> Polymath<E, T, V>
+where
+    E: Pairing<ScalarField = F>,
+    T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
+{
+    /// Find the first row of the SAP matrices in `pk` where `(Uz)² = Wz` doesn't hold
+    /// for the assignment `instance`, `witness`, or `None` if the assignment satisfies them all.
+    ///
+    /// `instance` and `witness` are as in [`Polymath::prove_with_assignment`].
+    /// The auxiliary part `y` of the SAP witness is derived from the assignment, so an R1CS
+    /// constraint `a·b ≠ c` shows up as its [`SapRow::ConstraintSum`] row.
+    pub fn check_assignment(
+        pk: &ProvingKey<E>,
+        instance: &[F],
+        witness: &[F],
+    ) -> Result<Option<UnsatisfiedConstraint>, PolymathError> {
+        if instance.len() + 1 != pk.sap_matrices.num_instance_variables() {
+            return Err(PolymathError::PublicInputLengthMismatch {
+                expected: pk.sap_matrices.num_instance_variables().saturating_sub(1),
+                got: instance.len(),
+            });
+        }
+        let instance_assignment = [&[F::one()], instance].concat();
+        Self::find_unsatisfied(pk, &instance_assignment, witness)
+    }
+
+    /// Same as [`Polymath::check_assignment`] for the assignment produced by synthesizing
+    /// `circuit`, with the namespace trace of the violated R1CS constraint when available.
+    pub fn check_circuit<C: ConstraintSynthesizer<F>>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+    ) -> Result<Option<UnsatisfiedConstraint>, PolymathError> {
+        let cs = Self::synthesize(circuit)?;
+        let unsatisfied = {
+            let prover = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+            Self::find_unsatisfied(pk, &prover.instance_assignment, &prover.witness_assignment)?
+        };
+
+        Ok(unsatisfied.map(|mut unsatisfied| {
+            if let SapRow::ConstraintSum { constraint }
+            | SapRow::ConstraintDifference { constraint } = unsatisfied.kind
+            {
+                unsatisfied.namespace = cs
+                    .constraint_names()
+                    .and_then(|names| names.into_iter().nth(constraint));
+            }
+            unsatisfied
+        }))
+    }
+
+    fn find_unsatisfied(
+        pk: &ProvingKey<E>,
+        instance_assignment: &[F],
+        witness_assignment: &[F],
+    ) -> Result<Option<UnsatisfiedConstraint>, PolymathError> {
+        Self::check_assignment_lengths(pk, instance_assignment, witness_assignment)?;
+
+        let z = [
+            instance_assignment,
+            instance_assignment,
+            witness_assignment,
+            &Self::compute_y_vec(pk, instance_assignment, witness_assignment),
+        ]
+        .concat();
+
+        let uz = Self::mul_vector(pk.sap_matrices.u_csc(), &z);
+        let wz = Self::mul_vector(pk.sap_matrices.w_csc(), &z);
+
+        let unsatisfied = uz
+            .iter()
+            .zip(&wz)
+            .position(|(uz_i, wz_i)| uz_i.square() != *wz_i)
+            .and_then(|sap_row| {
+                Some(UnsatisfiedConstraint {
+                    sap_row,
+                    kind: pk.sap_matrices.row(sap_row)?,
+                    namespace: None,
+                })
+            });
+        Ok(unsatisfied)
+    }
+
+    /// `M·z` for the sparse matrix `m`.
+    fn mul_vector(m: &CscMatrix<F>, z: &[F]) -> Vec<F> {
+        let mut result = vec![F::zero(); m.num_rows()];
+        for (j, &zj) in z.iter().enumerate() {
+            for (i, mij) in m.column(j) {
+                result[i] += mij * zj;
+            }
+        }
+        result
+    }
+}
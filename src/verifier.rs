@@ -1,38 +1,211 @@
-use ark_ec::{pairing::Pairing, VariableBaseMSM};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::PrimeField;
-use ark_std::One;
+use ark_std::{rand::RngCore, vec::Vec, One};
 
 use crate::{
-    common::{B_POLYMATH, MINUS_ALPHA, MINUS_GAMMA},
-    Polymath, PolymathError, Transcript, VerifyingKey,
+    common::{MINUS_ALPHA, MINUS_GAMMA},
+    FiatShamirVersion, Polymath, PolymathError, PreparedVerifyingKey, Transcript, VerifyingKey,
 };
 
 use super::Proof;
 
-impl<F: PrimeField, E, T> Polymath<E, T>
+impl<F: PrimeField, E, T, V> Polymath<E, T, V>
 where
     E: Pairing<ScalarField = F>,
     T: Transcript<Challenge = F>,
+    V: FiatShamirVersion,
 {
+    /// Prepare the verification key `vk` for use in proof verification.
+    pub fn prepare_verifying_key(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
+        let (n_inv, omega_powers) = vk.pi_constants();
+        PreparedVerifyingKey {
+            vk: vk.clone(),
+            z_g2: vk.e.z_g2.into(),
+            x_g2: vk.e.x_g2.into(),
+            one_g2: vk.e.one_g2.into(),
+            n_inv,
+            omega_powers,
+        }
+    }
+
+    /// Verify a Polymath proof `proof` against the prepared verification key `pvk`,
+    /// with respect to the instance `public_inputs` and the context `ctx` the proof is bound to.
+    pub(crate) fn verify_proof(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[F],
+        ctx: Option<&[u8]>,
+    ) -> Result<bool, PolymathError> {
+        let (commitments_minus_evals_in_g1, x1) =
+            Self::compute_pairing_inputs(pvk, proof, public_inputs, ctx)?;
+
+        Ok(Self::check_pairing(
+            pvk,
+            commitments_minus_evals_in_g1,
+            proof.d_g1,
+            x1,
+        ))
+    }
+
     /// Verify a Polymath proof `proof` against the verification key `vk`,
     /// with respect to the instance `public_inputs`.
-    pub(crate) fn verify_proof(
+    ///
+    /// Same as `SNARK::verify`, but also available in verifier-only builds,
+    /// where [`Polymath`] doesn't implement `SNARK`.
+    pub fn verify_with_vk(
         vk: &VerifyingKey<E>,
+        public_inputs: &[F],
         proof: &Proof<E>,
+    ) -> Result<bool, PolymathError> {
+        Self::verify_proof(&Self::prepare_verifying_key(vk), proof, public_inputs, None)
+    }
+
+    /// Verify a Polymath proof `proof` against the verification key `vk`,
+    /// with respect to the instance `public_inputs`, after checking the proof elements
+    /// with [`Proof::validate`].
+    ///
+    /// Use this for proofs that were deserialized without validation.
+    pub fn verify_proof_strict(
+        vk: &VerifyingKey<E>,
+        public_inputs: &[F],
+        proof: &Proof<E>,
+    ) -> Result<bool, PolymathError> {
+        proof.validate()?;
+        Self::verify_proof(&Self::prepare_verifying_key(vk), proof, public_inputs, None)
+    }
+
+    /// Verify a Polymath proof `proof` created with [`Polymath::prove_with_context`]
+    /// against the verification key `vk`, with respect to the instance `public_inputs`
+    /// and the context `ctx`.
+    ///
+    /// Fails if `ctx` differs from the one the proof was created with,
+    /// and for proofs created without a context.
+    pub fn verify_with_context(
+        vk: &VerifyingKey<E>,
         public_inputs: &[F],
+        proof: &Proof<E>,
+        ctx: &[u8],
     ) -> Result<bool, PolymathError> {
-        let mut t = T::new(B_POLYMATH);
+        Self::verify_proof(
+            &Self::prepare_verifying_key(vk),
+            proof,
+            public_inputs,
+            Some(ctx),
+        )
+    }
+
+    /// Verify many Polymath proofs against the same verification key `vk`.
+    /// Each item of `instances` is a pair of public inputs and a proof for them.
+    ///
+    /// The pairing equations of all proofs are combined with random coefficients from `rng`
+    /// into a single multi-pairing of size 3. If the combined check fails,
+    /// the proofs are checked one by one and the index of the first invalid proof is returned in
+    /// [`PolymathError::BatchVerificationFailed`]. So is the index of the first instance
+    /// with the wrong number of public inputs, before any pairing.
+    ///
+    /// Pairing-based aggregation doesn't do better. SnarkPack-style log-size aggregation
+    /// proves the random linear combinations of the proof elements instead of sending them,
+    /// which works for Groth16 because its pairing check has no per-proof challenges.
+    /// Here, the challenges `x1ᵢ` and `x2ᵢ` are hashes of `[aᵢ]₁` and `[cᵢ]₁`,
+    /// so a SnarkPack-style verifier needs all of them and its aggregate stays `O(N)`.
+    /// Recursion is not limited this way: a proof of a circuit running the verifier gadget
+    /// (`r1cs` feature) on the `N` proofs has constant size.
+    pub fn verify_batch<R: RngCore>(
+        vk: &VerifyingKey<E>,
+        instances: &[(&[F], &Proof<E>)],
+        rng: &mut R,
+    ) -> Result<(), PolymathError> {
+        let pvk = Self::prepare_verifying_key(vk);
+
+        let pairing_inputs = instances
+            .iter()
+            .enumerate()
+            .map(|(index, (public_inputs, proof))| {
+                Self::compute_pairing_inputs(&pvk, proof, public_inputs, None).map_err(
+                    |e| match e {
+                        PolymathError::PublicInputLengthMismatch { .. } => {
+                            PolymathError::BatchVerificationFailed { index }
+                        },
+                        e => e,
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let r: Vec<F> = (0..instances.len()).map(|_| F::rand(rng)).collect();
+        let r_x1: Vec<F> = r
+            .iter()
+            .zip(&pairing_inputs)
+            .map(|(r, (_, x1))| *r * x1)
+            .collect();
+
+        let commitments_minus_evals: Vec<E::G1> = pairing_inputs.iter().map(|(p, _)| *p).collect();
+        let d_g1: Vec<E::G1Affine> = instances.iter().map(|(_, proof)| proof.d_g1).collect();
+
+        let commitments_minus_evals_in_g1 =
+            E::G1::msm_unchecked(&E::G1::normalize_batch(&commitments_minus_evals), &r);
+        let d_in_g1 = E::G1::msm_unchecked(&d_g1, &r);
+        let x1_d_in_g1 = E::G1::msm_unchecked(&d_g1, &r_x1);
+
+        // e(∑ rᵢ·Pᵢ, [z]₂) = e(∑ rᵢ·[dᵢ]₁, [x]₂) - e(∑ rᵢ·x1ᵢ·[dᵢ]₁, [1]₂)
+        let pairing_output = E::multi_pairing(
+            [
+                commitments_minus_evals_in_g1.into_affine(),
+                (-d_in_g1).into_affine(),
+                x1_d_in_g1.into_affine(),
+            ],
+            [pvk.z_g2.clone(), pvk.x_g2.clone(), pvk.one_g2.clone()],
+        );
+        if pairing_output.0.is_one() {
+            return Ok(());
+        }
+
+        for (index, ((p, x1), (_, proof))) in pairing_inputs.into_iter().zip(instances).enumerate()
+        {
+            if !Self::check_pairing(&pvk, p, proof.d_g1, x1) {
+                return Err(PolymathError::BatchVerificationFailed { index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the `G1` side of the pairing check `P = [a]₁ + x2·[c]₁ - (A(x1) + x2·C(x1))·[1]₁`
+    /// together with the challenge `x1`.
+    fn compute_pairing_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[F],
+        ctx: Option<&[u8]>,
+    ) -> Result<(E::G1, F), PolymathError> {
+        let vk = &pvk.vk;
+
+        // `vk.m0` counts the leading `1`, which is not passed by the caller
+        if public_inputs.len() + 1 != vk.m0 as usize {
+            return Err(PolymathError::PublicInputLengthMismatch {
+                expected: (vk.m0 as usize).saturating_sub(1),
+                got: public_inputs.len(),
+            });
+        }
+
+        let mut t = Self::new_transcript(vk)?;
 
         let public_inputs = &[&[F::one()], public_inputs].concat();
 
         // compute challenge x1
-        let x1: F = Self::compute_x1(&mut t, public_inputs, &[proof.a_g1, proof.c_g1])?;
+        let x1: F = Self::compute_x1(&mut t, ctx, public_inputs, &[proof.a_g1, proof.c_g1])?;
 
         // compute y1=x1^sigma
         let y1: F = Self::compute_y1(x1, vk.sigma);
 
         let y1_gamma = Self::neg_power(y1, MINUS_GAMMA);
-        let pi_at_x1 = Self::compute_pi_at_x1(vk, public_inputs, x1, y1_gamma);
+        let pi_at_x1 = Self::compute_pi_at_x1(
+            vk,
+            pvk.n_inv,
+            &pvk.omega_powers,
+            public_inputs,
+            x1,
+            y1_gamma,
+        );
 
         let y1_alpha = Self::neg_power(y1, MINUS_ALPHA);
 
@@ -45,19 +218,23 @@ where
             &[proof.a_g1, proof.c_g1, vk.e.one_g1],
             &[F::one(), x2, -(proof.a_at_x1 + x2 * c_at_x1)],
         );
-        let x_minus_x1_in_g2 = E::G2::msm_unchecked(&[vk.e.x_g2, vk.e.one_g2], &[F::one(), -x1]);
 
+        Ok((commitments_minus_evals_in_g1, x1))
+    }
+
+    /// Check `e(P, [z]₂) = e([d]₁, [x]₂ - x1·[1]₂)`, with the right-hand side split into
+    /// `e([d]₁, [x]₂) - e(x1·[d]₁, [1]₂)` to pair against prepared `G2` elements only.
+    fn check_pairing(
+        pvk: &PreparedVerifyingKey<E>,
+        commitments_minus_evals_in_g1: E::G1,
+        d_g1: E::G1Affine,
+        x1: F,
+    ) -> bool {
         let pairing_output = E::multi_pairing(
-            [
-                <E::G1 as Into<E::G1Prepared>>::into(commitments_minus_evals_in_g1),
-                <E::G1 as Into<E::G1Prepared>>::into(proof.d_g1 * (-F::one())),
-            ],
-            [
-                <E::G2 as Into<E::G2Prepared>>::into(vk.e.z_g2.into()),
-                <E::G2 as Into<E::G2Prepared>>::into(x_minus_x1_in_g2),
-            ],
+            [commitments_minus_evals_in_g1, -d_g1.into_group(), d_g1 * x1],
+            [pvk.z_g2.clone(), pvk.x_g2.clone(), pvk.one_g2.clone()],
         );
 
-        Ok(pairing_output.0.is_one())
+        pairing_output.0.is_one()
     }
 }
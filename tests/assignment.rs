@@ -0,0 +1,61 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn prove_with_assignment() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let instance = [a * b];
+    let witness = [a, b];
+
+    let proof = Polymath::prove_with_assignment(&pk, &instance, &witness, &mut rng).unwrap();
+    assert!(Polymath::verify(&vk, &instance, &proof).unwrap());
+
+    assert!(matches!(
+        Polymath::prove_with_assignment(&pk, &[a * b, a], &witness, &mut rng),
+        Err(PolymathError::PublicInputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    ));
+    assert!(matches!(
+        Polymath::prove_with_assignment(&pk, &instance, &[a], &mut rng),
+        Err(PolymathError::WitnessLengthMismatch {
+            expected: 2,
+            got: 1
+        })
+    ));
+}
@@ -0,0 +1,73 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{keccak256::Keccak256Transcript, PolymathError};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, Keccak256Transcript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn test_verify_batch() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let mut instances = vec![];
+    for _ in 0..5 {
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let circuit = DummyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+        let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+        instances.push((vec![a * b], proof));
+    }
+
+    let batch: Vec<_> = instances
+        .iter()
+        .map(|(inputs, proof)| (inputs.as_slice(), proof))
+        .collect();
+    Polymath::verify_batch(&vk, &batch, &mut rng).unwrap();
+    Polymath::verify_batch(&vk, &[], &mut rng).unwrap();
+
+    let wrong_inputs = vec![Fr::rand(&mut rng)];
+    let mut batch = batch;
+    batch[3].0 = &wrong_inputs;
+    assert!(matches!(
+        Polymath::verify_batch(&vk, &batch, &mut rng),
+        Err(PolymathError::BatchVerificationFailed { index: 3 })
+    ));
+
+    // too many public inputs for the key
+    let long_inputs = vec![Fr::rand(&mut rng); 2];
+    batch[1].0 = &long_inputs;
+    assert!(matches!(
+        Polymath::verify_batch(&vk, &batch, &mut rng),
+        Err(PolymathError::BatchVerificationFailed { index: 1 })
+    ));
+}
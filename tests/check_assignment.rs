@@ -0,0 +1,127 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_ff::{Field, One};
+use ark_relations::{
+    lc, ns,
+    r1cs::{ConstraintLayer, ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError, SapRow};
+use tracing_subscriber::layer::SubscriberExt;
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+/// `c = a·b`, `d = c·b` with public `d`. `d` is assigned `d_offset` more than `c·b`.
+struct Circuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    d_offset: F,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for Circuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let c_val = self.a.and_then(|a| self.b.map(|b| a * b));
+        let d_val = c_val.and_then(|c| self.b.map(|b| c * b + self.d_offset));
+
+        let d = cs.new_input_variable(|| d_val.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_witness_variable(|| c_val.ok_or(SynthesisError::AssignmentMissing))?;
+
+        {
+            let cs = ns!(cs, "first");
+            cs.cs()
+                .enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        }
+        {
+            let cs = ns!(cs, "second");
+            cs.cs()
+                .enforce_constraint(lc!() + c, lc!() + b, lc!() + d)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn violated_constraint_is_located() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = Circuit::<Fr> {
+        a: None,
+        b: None,
+        d_offset: Fr::from(0),
+    };
+    let (pk, _) = Polymath::setup(circuit, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let c = a * b;
+    let d = c * b;
+
+    assert_eq!(
+        Polymath::check_assignment(&pk, &[d], &[a, b, c]).unwrap(),
+        None
+    );
+
+    // m₀ = 2, so the R1CS constraints start at SAP row 4
+    let unsatisfied = Polymath::check_assignment(&pk, &[d], &[a, b, c + Fr::one()])
+        .unwrap()
+        .unwrap();
+    assert_eq!(unsatisfied.sap_row, 4);
+    assert_eq!(unsatisfied.kind, SapRow::ConstraintSum { constraint: 0 });
+    assert_eq!(unsatisfied.namespace, None);
+
+    let unsatisfied = Polymath::check_assignment(&pk, &[d + Fr::one()], &[a, b, c])
+        .unwrap()
+        .unwrap();
+    assert_eq!(unsatisfied.kind, SapRow::ConstraintSum { constraint: 1 });
+
+    assert!(matches!(
+        Polymath::check_assignment(&pk, &[d], &[a, b]),
+        Err(PolymathError::WitnessLengthMismatch {
+            expected: 3,
+            got: 2
+        })
+    ));
+
+    assert_eq!(
+        pk.sap_matrices.row(0),
+        Some(SapRow::PublicInputSum { input: 0 })
+    );
+    assert_eq!(
+        pk.sap_matrices.row(3),
+        Some(SapRow::PublicInputDifference { input: 1 })
+    );
+    assert_eq!(
+        pk.sap_matrices.row(7),
+        Some(SapRow::ConstraintDifference { constraint: 1 })
+    );
+    assert_eq!(pk.sap_matrices.row(8), None);
+}
+
+#[test]
+fn violated_constraint_namespace_is_reported() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = Circuit::<Fr> {
+        a: None,
+        b: None,
+        d_offset: Fr::from(0),
+    };
+    let (pk, _) = Polymath::setup(circuit, &mut rng).unwrap();
+
+    let circuit = Circuit {
+        a: Some(Fr::rand(&mut rng)),
+        b: Some(Fr::rand(&mut rng)),
+        d_offset: Fr::one(),
+    };
+    let subscriber = tracing_subscriber::Registry::default().with(ConstraintLayer::default());
+    let unsatisfied = tracing::subscriber::with_default(subscriber, || {
+        Polymath::check_circuit(&pk, circuit).unwrap().unwrap()
+    });
+    assert_eq!(unsatisfied.kind, SapRow::ConstraintSum { constraint: 1 });
+    assert!(unsatisfied.namespace.unwrap().contains("second"));
+}
@@ -0,0 +1,63 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::keccak256::Keccak256Transcript;
+
+type Polymath = charms_polymath::Polymath<Bls12_381, Keccak256Transcript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn proof_is_bound_to_context() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let public_inputs = [a * b];
+    let txid = [0x42u8; 32];
+
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove_with_context(&pk, circuit, &txid, &mut rng).unwrap();
+
+    assert!(Polymath::verify_with_context(&vk, &public_inputs, &proof, &txid).unwrap());
+    assert!(!Polymath::verify_with_context(&vk, &public_inputs, &proof, &[0x43; 32]).unwrap());
+    assert!(!Polymath::verify_with_context(&vk, &public_inputs, &proof, &txid[..31]).unwrap());
+    assert!(!Polymath::verify(&vk, &public_inputs, &proof).unwrap());
+
+    // a proof without a context doesn't verify with an empty one
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Polymath::verify(&vk, &public_inputs, &proof).unwrap());
+    assert!(!Polymath::verify_with_context(&vk, &public_inputs, &proof, &[]).unwrap());
+}
@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.19;
+
+/// @title Polymath verifier
+/// @notice Verifies Polymath proofs over BN254 against a single verifying key.
+/// @dev Generated by charms-polymath. The Fiat-Shamir challenges are computed exactly as
+/// by `Keccak256Transcript`: each label and message is prefixed with its length as
+/// a big-endian u64, and the messages are the ABI-encoded uint256 words of the field elements
+/// and of the coordinates of the G1 points, lists starting with their length. A challenge is
+/// the first 48 bytes of keccak256(h | 0x00) | keccak256(h | 0x01), with h the hash of
+/// the transcript, reduced modulo r (see `wideChallenge`).
+contract PolymathVerifier {
+    uint256 internal constant R = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;
+    uint256 internal constant Q = 0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47;
+
+    uint256 internal constant N = 16;
+    uint256 internal constant M0 = 3;
+    uint256 internal constant SIGMA = 19;
+    uint256 internal constant OMEGA = 0x21082ca216cbbf4e1c6e4f4594dd508c996dfbe1174efb98b11509c6e306460b;
+    uint256 internal constant N_INV = 0x2d5e098bb31e86271ccb415b196942d755b0a9c3f21dd9882fa3d63ab1000001;
+
+    uint256 internal constant ONE_G1_X = 0x0000000000000000000000000000000000000000000000000000000000000001;
+    uint256 internal constant ONE_G1_Y = 0x0000000000000000000000000000000000000000000000000000000000000002;
+    uint256 internal constant ONE_G2_X_IM = 0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2;
+    uint256 internal constant ONE_G2_X_RE = 0x1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed;
+    uint256 internal constant ONE_G2_Y_IM = 0x090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b;
+    uint256 internal constant ONE_G2_Y_RE = 0x12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa;
+    uint256 internal constant X_G2_X_IM = 0x1d52350c6e014bf3f1b90026ae0a75a9ba5fcfa50b5c5f52fece0ad24acf4e6a;
+    uint256 internal constant X_G2_X_RE = 0x0141e0464c49c63c15dd475027f1a1cccc107ec224fad6c8ab4065da5d88f4d4;
+    uint256 internal constant X_G2_Y_IM = 0x2683178135d539b42dd3447214eb761f45b11289f5eb5464404388eda9a0b6e8;
+    uint256 internal constant X_G2_Y_RE = 0x19b02df8eb9ceaf16944205ceaee2ead460951e5dc9a2aca6f0e1c1f7fbc4c84;
+    uint256 internal constant Z_G2_X_IM = 0x2b965ba5f6cb42abc3ff57877eb3f2ffa52ffba6be6a425784b2884543da1277;
+    uint256 internal constant Z_G2_X_RE = 0x0aeb50b20b47e9c710bcd1f5e12ea7499bc4ceaa11ddc2942708d1d00b7a29b8;
+    uint256 internal constant Z_G2_Y_IM = 0x0bb846560074e04656b900aecb73d42f9f01e58ad4394645da4a36cc30781869;
+    uint256 internal constant Z_G2_Y_RE = 0x2d9b9c5f6f04ac5ae3a6ebe67cdb8b628b92b0a558e18c4ea1e64105ceb06152;
+
+    bytes internal constant TRANSCRIPT_PREFIX = hex"0000000000000008706f6c796d617468000000000000001070726f746f636f6c5f76657273696f6e000000000000000801000000000000000000000000000009766b5f6469676573740000000000000020737b6385e01cb783369c28b9954c187996a2c89694ddc408c54f2af606b8ae25";
+
+    /// @notice Verify a proof for the public inputs `input`.
+    /// @param proof [a]_1.x, [a]_1.y, [c]_1.x, [c]_1.y, A(x1), [d]_1.x, [d]_1.y
+    /// @param input the public inputs, without the leading 1
+    function verifyProof(uint256[7] calldata proof, uint256[] calldata input)
+        public
+        view
+        returns (bool)
+    {
+        if (input.length + 1 != M0) return false;
+        for (uint256 i = 0; i < 7; i++) {
+            if (proof[i] >= (i == 4 ? R : Q)) return false;
+        }
+        for (uint256 i = 0; i < input.length; i++) {
+            if (input[i] >= R) return false;
+        }
+
+        (bytes32 state, uint256 x1) = challengeX1(proof, input);
+        uint256 cAtX1 = evaluateC(input, x1, proof[4]);
+        uint256 x2 = challengeX2(state, x1, proof[4], cAtX1);
+        return checkPairing(proof, x1, x2, cAtX1);
+    }
+
+    /// @dev x1 from keccak256(prefix | "public_inputs" | [1, input] | "commitments" | [a]_1, [c]_1 | "x1"),
+    /// with the labels and the messages prefixed with their length as a big-endian u64
+    function challengeX1(uint256[7] calldata proof, uint256[] calldata input)
+        internal
+        pure
+        returns (bytes32 state, uint256 x1)
+    {
+        state = keccak256(
+            abi.encodePacked(
+                TRANSCRIPT_PREFIX,
+                uint64(13),
+                "public_inputs",
+                uint64(32 + 32 * M0),
+                M0,
+                uint256(1),
+                input,
+                uint64(11),
+                "commitments",
+                uint64(32 + 4 * 32),
+                uint256(2),
+                proof[0],
+                proof[1],
+                proof[2],
+                proof[3],
+                uint64(2),
+                "x1"
+            )
+        );
+        x1 = wideChallenge(state);
+    }
+
+    /// @dev x2 from keccak256(state | "x1" | x1 | "values" | A(x1), C(x1) | "x2"),
+    /// with the labels and the messages prefixed with their length as a big-endian u64
+    function challengeX2(bytes32 state, uint256 x1, uint256 aAtX1, uint256 cAtX1)
+        internal
+        pure
+        returns (uint256)
+    {
+        bytes32 h = keccak256(
+            abi.encodePacked(
+                state,
+                uint64(2),
+                "x1",
+                uint64(32),
+                x1,
+                uint64(6),
+                "values",
+                uint64(32 + 2 * 32),
+                uint256(2),
+                aAtX1,
+                cAtX1,
+                uint64(2),
+                "x2"
+            )
+        );
+        return wideChallenge(h);
+    }
+
+    /// @dev The first 48 bytes of keccak256(h | 0x00) | keccak256(h | 0x01) as a big-endian
+    /// integer, reduced modulo R: hi * 2^128 + lo, with hi the first 32 bytes and lo the next 16.
+    function wideChallenge(bytes32 h) internal pure returns (uint256) {
+        uint256 hi = uint256(keccak256(abi.encodePacked(h, uint8(0))));
+        uint256 lo = uint256(keccak256(abi.encodePacked(h, uint8(1)))) >> 128;
+        return addmod(mulmod(hi, 1 << 128, R), lo, R);
+    }
+
+    /// @dev C(x1) = ((A(x1) + y1^gamma) * A(x1) - PI(x1)) / y1^alpha, with y1 = x1^sigma,
+    /// alpha = -3 and gamma = -5.
+    function evaluateC(uint256[] calldata input, uint256 x1, uint256 aAtX1)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256 y1 = expMod(x1, SIGMA);
+        uint256 y1Inv = expMod(y1, R - 2);
+        uint256 y1Gamma = mulmod(mulmod(y1Inv, y1Inv, R), y1Inv, R);
+        y1Gamma = mulmod(mulmod(y1Gamma, y1Inv, R), y1Inv, R);
+        uint256 y1Cube = mulmod(mulmod(y1, y1, R), y1, R);
+
+        uint256 pi = evaluatePi(input, x1, y1Gamma);
+        uint256 lhs = mulmod(addmod(aAtX1, y1Gamma, R), aAtX1, R);
+        return mulmod(addmod(lhs, R - pi, R), y1Cube, R);
+    }
+
+    /// @dev PI(x1) = sum_i z_i * w^i / (x1 - w^i) * (x1^n - 1) / n * y1^gamma for i in [0, 2 * m0),
+    /// with z_0 = 2, z_i = 1 + x_i for i < m0, z_m0 = 0 and z_i = 1 - x_(i - m0) otherwise.
+    /// The sum is accumulated as a single fraction to invert only once.
+    function evaluatePi(uint256[] calldata input, uint256 x1, uint256 y1Gamma)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256 num = 0;
+        uint256 den = 1;
+        uint256 w = 1;
+        for (uint256 i = 0; i < 2 * M0; i++) {
+            uint256 z;
+            if (i == 0) {
+                z = 2;
+            } else if (i < M0) {
+                z = addmod(1, input[i - 1], R);
+            } else if (i == M0) {
+                z = 0;
+            } else {
+                z = addmod(1, R - input[i - M0 - 1], R);
+            }
+            uint256 d = addmod(x1, R - w, R);
+            num = addmod(mulmod(num, d, R), mulmod(mulmod(z, w, R), den, R), R);
+            den = mulmod(den, d, R);
+            w = mulmod(w, OMEGA, R);
+        }
+        uint256 vanishing = addmod(expMod(x1, N), R - 1, R);
+        uint256 sum = mulmod(num, expMod(den, R - 2), R);
+        return mulmod(mulmod(sum, vanishing, R), mulmod(N_INV, y1Gamma, R), R);
+    }
+
+    /// @dev e(P, [z]_2) = e([d]_1, [x]_2) - e(x1 * [d]_1, [1]_2),
+    /// with P = [a]_1 + x2 * [c]_1 - (A(x1) + x2 * C(x1)) * [1]_1
+    function checkPairing(uint256[7] calldata proof, uint256 x1, uint256 x2, uint256 cAtX1)
+        internal
+        view
+        returns (bool)
+    {
+        uint256[18] memory input;
+        bool ok;
+        uint256 s = addmod(proof[4], mulmod(x2, cAtX1, R), R);
+        (ok, input[0], input[1]) = ecMul(ONE_G1_X, ONE_G1_Y, (R - s) % R);
+        if (!ok) return false;
+        (ok, input[2], input[3]) = ecMul(proof[2], proof[3], x2);
+        if (!ok) return false;
+        (ok, input[0], input[1]) = ecAdd(input[0], input[1], input[2], input[3]);
+        if (!ok) return false;
+        (ok, input[0], input[1]) = ecAdd(input[0], input[1], proof[0], proof[1]);
+        if (!ok) return false;
+        input[2] = Z_G2_X_IM;
+        input[3] = Z_G2_X_RE;
+        input[4] = Z_G2_Y_IM;
+        input[5] = Z_G2_Y_RE;
+
+        input[6] = proof[5];
+        input[7] = (Q - proof[6]) % Q;
+        input[8] = X_G2_X_IM;
+        input[9] = X_G2_X_RE;
+        input[10] = X_G2_Y_IM;
+        input[11] = X_G2_Y_RE;
+
+        (ok, input[12], input[13]) = ecMul(proof[5], proof[6], x1);
+        if (!ok) return false;
+        input[14] = ONE_G2_X_IM;
+        input[15] = ONE_G2_X_RE;
+        input[16] = ONE_G2_Y_IM;
+        input[17] = ONE_G2_Y_RE;
+
+        uint256[1] memory out;
+        assembly ("memory-safe") {
+            ok := staticcall(gas(), 0x08, input, 576, out, 0x20)
+        }
+        return ok && out[0] == 1;
+    }
+
+    function expMod(uint256 base, uint256 e) internal view returns (uint256 result) {
+        bool ok;
+        assembly ("memory-safe") {
+            let p := mload(0x40)
+            mstore(p, 0x20)
+            mstore(add(p, 0x20), 0x20)
+            mstore(add(p, 0x40), 0x20)
+            mstore(add(p, 0x60), base)
+            mstore(add(p, 0x80), e)
+            mstore(add(p, 0xa0), R)
+            ok := staticcall(gas(), 0x05, p, 0xc0, p, 0x20)
+            result := mload(p)
+        }
+        require(ok, "modexp failed");
+    }
+
+    function ecAdd(uint256 ax, uint256 ay, uint256 bx, uint256 by)
+        internal
+        view
+        returns (bool ok, uint256 x, uint256 y)
+    {
+        assembly ("memory-safe") {
+            let p := mload(0x40)
+            mstore(p, ax)
+            mstore(add(p, 0x20), ay)
+            mstore(add(p, 0x40), bx)
+            mstore(add(p, 0x60), by)
+            ok := staticcall(gas(), 0x06, p, 0x80, p, 0x40)
+            x := mload(p)
+            y := mload(add(p, 0x20))
+        }
+    }
+
+    function ecMul(uint256 ax, uint256 ay, uint256 s)
+        internal
+        view
+        returns (bool ok, uint256 x, uint256 y)
+    {
+        assembly ("memory-safe") {
+            let p := mload(0x40)
+            mstore(p, ax)
+            mstore(add(p, 0x20), ay)
+            mstore(add(p, 0x40), s)
+            ok := staticcall(gas(), 0x07, p, 0x60, p, 0x40)
+            x := mload(p)
+            y := mload(add(p, 0x20))
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, One, UniformRand,
+};
+use charms_polymath::{
+    blake3::{Blake3Transcript, LegacyBlake3Transcript},
+    keccak256::LegacyKeccak256Transcript,
+    FiatShamirV0, FiatShamirV1, Polymath, Proof, ProvingKey,
+};
+
+type T = Blake3Transcript<Fr>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+fn prove<S: SNARK<Fr, ProvingKey = ProvingKey<Bls12_381>, Proof = Proof<Bls12_381>>>(
+    pk: &ProvingKey<Bls12_381>,
+    rng: &mut StdRng,
+) -> (Fr, Proof<Bls12_381>) {
+    let a = Fr::rand(rng);
+    let b = Fr::rand(rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    (a * b, S::prove(pk, circuit, rng).ok().unwrap())
+}
+
+#[test]
+fn versions_are_not_interchangeable() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> { a: None, b: None };
+    let (pk, vk) = Polymath::<Bls12_381, T>::setup(circuit, &mut rng).unwrap();
+
+    let (c, proof) = prove::<Polymath<Bls12_381, T>>(&pk, &mut rng);
+    assert!(Polymath::<Bls12_381, T, FiatShamirV1>::verify(&vk, &[c], &proof).unwrap());
+    assert!(!Polymath::<Bls12_381, T, FiatShamirV0>::verify(&vk, &[c], &proof).unwrap());
+
+    let (c, proof) = prove::<Polymath<Bls12_381, T, FiatShamirV0>>(&pk, &mut rng);
+    assert!(Polymath::<Bls12_381, T, FiatShamirV0>::verify(&vk, &[c], &proof).unwrap());
+    assert!(!Polymath::<Bls12_381, T, FiatShamirV1>::verify(&vk, &[c], &proof).unwrap());
+}
+
+#[test]
+fn legacy_transcripts_prove_with_v0() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> { a: None, b: None };
+    let (pk, vk) = Polymath::<Bls12_381, T>::setup(circuit, &mut rng).unwrap();
+
+    type Keccak256V0 = Polymath<Bls12_381, LegacyKeccak256Transcript<Fr>, FiatShamirV0>;
+    let (c, proof) = prove::<Keccak256V0>(&pk, &mut rng);
+    assert!(Keccak256V0::verify(&vk, &[c], &proof).unwrap());
+
+    type Blake3V0 = Polymath<Bls12_381, LegacyBlake3Transcript<Fr>, FiatShamirV0>;
+    let (c, proof) = prove::<Blake3V0>(&pk, &mut rng);
+    assert!(Blake3V0::verify(&vk, &[c], &proof).unwrap());
+}
+
+#[test]
+fn digest_covers_every_key_parameter() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> { a: None, b: None };
+    let (_, vk) = Polymath::<Bls12_381, T>::setup(circuit, &mut rng).unwrap();
+    let digest = vk.digest().unwrap();
+    assert_eq!(vk.clone().digest().unwrap(), digest);
+
+    let mut other = vk.clone();
+    other.n *= 2;
+    assert_ne!(other.digest().unwrap(), digest);
+
+    let mut other = vk.clone();
+    other.m0 += 1;
+    assert_ne!(other.digest().unwrap(), digest);
+
+    let mut other = vk.clone();
+    other.sigma += 1;
+    assert_ne!(other.digest().unwrap(), digest);
+
+    let mut other = vk.clone();
+    other.omega += Fr::one();
+    assert_ne!(other.digest().unwrap(), digest);
+
+    let mut other = vk.clone();
+    other.e.z_g2 = vk.e.x_g2;
+    assert_ne!(other.digest().unwrap(), digest);
+}
@@ -0,0 +1,190 @@
+use ark_bls12_381::{Fq, Fr, G1Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, One, PrimeField};
+use blake3::Hasher;
+use charms_polymath::{
+    blake3::{Blake3Transcript, LegacyBlake3Transcript},
+    keccak256::{Keccak256Transcript, LegacyKeccak256Transcript},
+    Transcript,
+};
+use sha3::{Digest, Keccak256};
+
+fn fr(hex: &str) -> Fr {
+    Fr::from_be_bytes_mod_order(&hex::decode(hex).unwrap())
+}
+
+fn challenges<T: Transcript<Challenge = Fr>>() -> (Fr, Fr) {
+    let mut t = T::new(b"polymath");
+    t.append_message(b"ab", b"c");
+    t.append_scalars(b"values", &[Fr::from(1u64), Fr::from(15u64)])
+        .unwrap();
+    t.append_points(b"commitments", &[G1Affine::generator()])
+        .unwrap();
+    let x1 = t.challenge(b"x1");
+    t.append_scalar(b"x1", &x1).unwrap();
+    let x2 = t.challenge(b"x2");
+    (x1, x2)
+}
+
+/// The legacy transcripts must keep producing the challenges of the proofs created before
+/// the name and the lengths were absorbed.
+#[test]
+fn legacy_challenges_are_unchanged() {
+    assert_eq!(
+        challenges::<LegacyKeccak256Transcript<Fr>>(),
+        (
+            fr("51baa19115131c508b564d6f6656ca9ae920b4ea4be9ab277e2326300dce0e6b"),
+            fr("17dc4edce30dd92f08e38dbd8a5ebc90d11d1df6618f2d929616f3d5af7fcdd3"),
+        )
+    );
+    assert_eq!(
+        challenges::<LegacyBlake3Transcript<Fr>>(),
+        (
+            fr("6fd4a38f8669a746fbc004009686cbd9d468082ffd2512fd2cba151a15d72fdf"),
+            fr("0ab63121eeb6222b1950c130f3aaa591d660d30ce5a8454b0435dac7c4894b7e"),
+        )
+    );
+}
+
+fn assert_separated<T: Transcript<Challenge = Fr>>() {
+    let mut t1 = T::new(b"polymath");
+    t1.append_message(b"ab", b"c");
+    let mut t2 = T::new(b"polymath");
+    t2.append_message(b"a", b"bc");
+    assert_ne!(t1.challenge(b"x"), t2.challenge(b"x"));
+
+    let mut t1 = T::new(b"polymath");
+    let mut t2 = T::new(b"other");
+    assert_ne!(t1.challenge(b"x"), t2.challenge(b"x"));
+}
+
+#[test]
+fn labels_messages_and_names_are_separated() {
+    assert_separated::<Keccak256Transcript<Fr>>();
+    assert_separated::<Blake3Transcript<Fr>>();
+
+    let mut t1 = LegacyKeccak256Transcript::<Fr>::new(b"polymath");
+    t1.append_message(b"ab", b"c");
+    let mut t2 = LegacyKeccak256Transcript::<Fr>::new(b"other");
+    t2.append_message(b"a", b"bc");
+    assert_eq!(t1.challenge(b"x"), t2.challenge(b"x"));
+}
+
+/// `T` after `new(b"polymath")`, `append_message(b"ab", b"c")` and the challenge label `x`.
+fn transcript_bytes() -> Vec<u8> {
+    [
+        &8u64.to_be_bytes()[..],
+        b"polymath",
+        &2u64.to_be_bytes(),
+        b"ab",
+        &1u64.to_be_bytes(),
+        b"c",
+        &1u64.to_be_bytes(),
+        b"x",
+    ]
+    .concat()
+}
+
+fn keccak256_challenge<F: PrimeField>() -> F {
+    let mut t = Keccak256Transcript::<F>::new(b"polymath");
+    t.append_message(b"ab", b"c");
+    t.challenge(b"x")
+}
+
+fn blake3_challenge<F: PrimeField>() -> F {
+    let mut t = Blake3Transcript::<F>::new(b"polymath");
+    t.append_message(b"ab", b"c");
+    t.challenge(b"x")
+}
+
+/// The challenges are the documented number of bytes of the wide output, reduced modulo `p`:
+/// 48 bytes for the 255 bits of `Fr`, and 64 for the 381 bits of `Fq`.
+#[test]
+fn challenges_are_derived_from_wide_outputs() {
+    let h = Keccak256::digest(transcript_bytes());
+    let wide = [0u8, 1]
+        .map(|counter| {
+            Keccak256::new()
+                .chain_update(h)
+                .chain_update([counter])
+                .finalize()
+        })
+        .concat();
+    assert_eq!(
+        keccak256_challenge::<Fr>(),
+        Fr::from_be_bytes_mod_order(&wide[..48])
+    );
+    assert_eq!(
+        keccak256_challenge::<Fq>(),
+        Fq::from_be_bytes_mod_order(&wide[..64])
+    );
+
+    let mut wide = [0u8; 64];
+    Hasher::new()
+        .update(&transcript_bytes())
+        .finalize_xof()
+        .fill(&mut wide);
+    assert_eq!(
+        blake3_challenge::<Fr>(),
+        Fr::from_be_bytes_mod_order(&wide[..48])
+    );
+    assert_eq!(
+        blake3_challenge::<Fq>(),
+        Fq::from_be_bytes_mod_order(&wide[..64])
+    );
+}
+
+fn word(bytes: &[u8]) -> Vec<u8> {
+    let mut w = vec![0u8; 32 - bytes.len()];
+    w.extend(bytes);
+    w
+}
+
+/// Field elements and points go in as the big-endian words of the EVM ABI.
+#[test]
+fn keccak256_absorbs_abi_words() {
+    let challenge = |t: &mut Keccak256Transcript<Fr>| t.challenge(b"x");
+    let x = Fr::from(15u64);
+    let (gx, gy) = G1Affine::generator().xy().unwrap();
+    let (gx, gy) = (
+        gx.into_bigint().to_bytes_be(),
+        gy.into_bigint().to_bytes_be(),
+    );
+
+    let mut t1 = Keccak256Transcript::<Fr>::new(b"polymath");
+    t1.append_scalar(b"s", &x).unwrap();
+    t1.append_scalars(b"v", &[Fr::one(), x]).unwrap();
+    t1.append_g1(b"p", &G1Affine::generator()).unwrap();
+    t1.append_points(b"ps", &[G1Affine::generator(), G1Affine::zero()])
+        .unwrap();
+
+    let mut t2 = Keccak256Transcript::<Fr>::new(b"polymath");
+    t2.append_message(b"s", word(&[15]));
+    t2.append_message(b"v", [word(&[2]), word(&[1]), word(&[15])].concat());
+    // the 381-bit coordinates take two words
+    t2.append_message(b"p", [&[0u8; 16][..], &gx, &[0u8; 16], &gy].concat());
+    t2.append_message(
+        b"ps",
+        [
+            &word(&[2])[..],
+            &[0u8; 16],
+            &gx,
+            &[0u8; 16],
+            &gy,
+            &[0u8; 128],
+        ]
+        .concat(),
+    );
+
+    assert_eq!(challenge(&mut t1), challenge(&mut t2));
+}
+
+/// Without an encoding of its own, a single point is appended as a list of one.
+#[test]
+fn append_g1_defaults_to_a_list_of_one() {
+    let mut t1 = Blake3Transcript::<Fr>::new(b"polymath");
+    t1.append_g1(b"p", &G1Affine::generator()).unwrap();
+    let mut t2 = Blake3Transcript::<Fr>::new(b"polymath");
+    t2.append_points(b"p", &[G1Affine::generator()]).unwrap();
+    assert_eq!(t1.challenge(b"x"), t2.challenge(b"x"));
+}
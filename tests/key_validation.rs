@@ -0,0 +1,98 @@
+use ark_bls12_381::{Bls12_381, Fr, G1Affine};
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_ec::AffineRepr;
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError, ProvingKey};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a + b, lc!() + b, lc!() + c + b + b)
+    }
+}
+
+fn setup(rng: &mut StdRng) -> ProvingKey<Bls12_381> {
+    let c = DummyCircuit::<Fr> { a: None, b: None };
+    Polymath::setup(c, rng).unwrap().0
+}
+
+#[test]
+fn valid_key_passes() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let pk = setup(&mut rng);
+
+    pk.validate().unwrap();
+    pk.validate_batched(&mut rng).unwrap();
+}
+
+#[test]
+fn tampered_elements_are_rejected() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let pk = setup(&mut rng);
+
+    let tamper = |v: &mut Vec<G1Affine>, i: usize| {
+        v[i] = (v[i] + G1Affine::generator()).into();
+    };
+
+    let mut bad = pk.clone();
+    tamper(&mut bad.x_powers_g1, 2);
+    assert!(matches!(bad.validate(), Err(PolymathError::InvalidKey(_))));
+    assert!(bad.validate_batched(&mut rng).is_err());
+
+    let mut bad = pk.clone();
+    tamper(&mut bad.x_powers_zh_by_y_alpha_g1, 0);
+    assert!(matches!(bad.validate(), Err(PolymathError::InvalidKey(_))));
+    assert!(bad.validate_batched(&mut rng).is_err());
+
+    let mut bad = pk.clone();
+    let last = bad.uj_wj_lcs_by_y_alpha_g1.len() - 1;
+    tamper(&mut bad.uj_wj_lcs_by_y_alpha_g1, last);
+    assert!(matches!(bad.validate(), Err(PolymathError::InvalidKey(_))));
+    assert!(bad.validate_batched(&mut rng).is_err());
+
+    let mut bad = pk.clone();
+    bad.vk.e.z_g2 = (bad.vk.e.z_g2 + bad.vk.e.one_g2).into();
+    assert!(matches!(bad.validate(), Err(PolymathError::InvalidKey(_))));
+    assert!(bad.validate_batched(&mut rng).is_err());
+}
+
+#[test]
+fn wrong_lengths_are_rejected() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let pk = setup(&mut rng);
+
+    let mut bad = pk.clone();
+    bad.x_powers_y_gamma_z_g1.pop();
+    assert!(matches!(
+        bad.validate(),
+        Err(PolymathError::InvalidKeyLength {
+            name: "x_powers_y_gamma_z_g1",
+            ..
+        })
+    ));
+
+    let mut bad = pk.clone();
+    bad.vk.sigma += 1;
+    assert!(matches!(bad.validate(), Err(PolymathError::InvalidKey(_))));
+}
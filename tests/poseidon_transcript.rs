@@ -0,0 +1,136 @@
+use ark_bls12_381::{Bls12_381, Fq, Fr, G1Affine};
+use ark_crypto_primitives::{
+    snark::{CircuitSpecificSetupSNARK, SNARK},
+    sponge::Absorb,
+};
+use ark_ec::AffineRepr;
+use ark_ff::{Field, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    fields::{emulated_fp::EmulatedFpVar, FieldVar},
+    groups::bls12::G1Var,
+    uint8::UInt8,
+    R1CSVar,
+};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{
+    constraints::TranscriptVar,
+    poseidon::{constraints::PoseidonTranscriptVar, PoseidonTranscript},
+    Transcript,
+};
+
+/// Absorb a message and field elements in both transcripts and compare the challenges.
+fn assert_scalar_challenges_match<F: PrimeField, CF: PrimeField + Absorb>(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let scalars: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+    let max = -F::one();
+
+    let mut t = PoseidonTranscript::<F, CF>::new(b"test");
+    t.append_message(b"message", b"hello");
+    t.append_scalars(b"scalars", &scalars).unwrap();
+    t.append_scalar(b"max", &max).unwrap();
+    let c1 = t.challenge(b"c1");
+    let c2 = t.challenge(b"c2");
+
+    let cs = ConstraintSystem::<CF>::new_ref();
+    let scalar_vars: Vec<_> = scalars
+        .iter()
+        .map(|s| EmulatedFpVar::new_witness(cs.clone(), || Ok(*s)).unwrap())
+        .collect();
+    let mut t_var = PoseidonTranscriptVar::<F, CF>::new(cs.clone(), b"test").unwrap();
+    t_var
+        .append_message(
+            b"message",
+            &UInt8::new_witness_vec(cs.clone(), b"hello").unwrap(),
+        )
+        .unwrap();
+    t_var.append_scalars(b"scalars", &scalar_vars).unwrap();
+    t_var
+        .append_scalar(b"max", &EmulatedFpVar::constant(max))
+        .unwrap();
+
+    assert_eq!(t_var.challenge(b"c1").unwrap().value().unwrap(), c1);
+    assert_eq!(t_var.challenge(b"c2").unwrap().value().unwrap(), c2);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn scalar_challenges_match_in_circuit() {
+    // a challenge whose top bit would be set only shows up every few seeds
+    for seed in 0..16 {
+        // scalar field elements fit in the base field
+        assert_scalar_challenges_match::<Fr, Fq>(seed);
+        // native
+        assert_scalar_challenges_match::<Fr, Fr>(seed);
+        // base field elements are split in two
+        assert_scalar_challenges_match::<Fq, Fr>(seed);
+    }
+}
+
+#[test]
+fn point_challenges_match_in_circuit() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let points = [G1Affine::rand(&mut rng), G1Affine::zero()];
+
+    let mut t = PoseidonTranscript::<Fr, Fq>::new(b"test");
+    t.append_points(b"points", &points).unwrap();
+    t.append_g1(b"point", &points[0]).unwrap();
+    let c = t.challenge(b"c");
+
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    let point_vars: Vec<_> = points
+        .iter()
+        .map(|p| {
+            G1Var::<ark_bls12_381::Config>::new_witness(cs.clone(), || Ok(p.into_group())).unwrap()
+        })
+        .collect();
+    let mut t_var = PoseidonTranscriptVar::<Fr, Fq>::new(cs.clone(), b"test").unwrap();
+    t_var.append_points(b"points", &point_vars).unwrap();
+    t_var.append_g1(b"point", &point_vars[0]).unwrap();
+
+    assert_eq!(t_var.challenge(b"c").unwrap().value().unwrap(), c);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn polymath_proof_verifies_with_poseidon_over_scalar_field() {
+    type Polymath = charms_polymath::Polymath<Bls12_381, PoseidonTranscript<Fr>>;
+
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+
+    assert!(Polymath::verify(&vk, &[a * b], &proof).unwrap());
+    assert!(!Polymath::verify(&vk, &[a], &proof).unwrap());
+}
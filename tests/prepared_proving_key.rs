@@ -0,0 +1,75 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::merlin::MerlinFieldTranscript;
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn prepared_proving_key_is_reused_across_proofs() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+    let ppk = Polymath::prepare_proving_key(&pk).unwrap();
+
+    // the rows of `U` and `W` are the columns of the transposed matrices
+    let (u, w) = (pk.sap_matrices.u_csc(), pk.sap_matrices.w_csc());
+    assert_eq!(pk.sap_matrices.u_rows().num_non_zeros(), u.num_non_zeros());
+    assert_eq!(pk.sap_matrices.w_rows().num_non_zeros(), w.num_non_zeros());
+    for i in 0..pk.sap_matrices.u_rows().num_columns() {
+        for (j, uij) in pk.sap_matrices.u_rows().column(i) {
+            assert_eq!(u.get(i, j), uij);
+        }
+        for (j, wij) in pk.sap_matrices.w_rows().column(i) {
+            assert_eq!(w.get(i, j), wij);
+        }
+    }
+    assert_eq!(&pk.sap_matrices.u_rows().transpose(), u);
+
+    for _ in 0..3 {
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let circuit = DummyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+
+        let proof = Polymath::prove_prepared(&ppk, circuit, None, &mut rng).unwrap();
+        assert!(Polymath::verify(&vk, &[a * b], &proof).unwrap());
+
+        let proof = Polymath::prove_prepared_with_assignment(
+            &ppk,
+            &[a * b],
+            &[a, b],
+            Some(b"tx"),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(Polymath::verify_with_context(&vk, &[a * b], &proof, b"tx").unwrap());
+    }
+}
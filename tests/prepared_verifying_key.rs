@@ -0,0 +1,90 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::{Field, One};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PreparedVerifyingKey};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+fn roundtrip(
+    pvk: &PreparedVerifyingKey<Bls12_381>,
+    validate: bool,
+) -> Result<PreparedVerifyingKey<Bls12_381>, SerializationError> {
+    let mut bytes = vec![];
+    pvk.serialize_compressed(&mut bytes).unwrap();
+    if validate {
+        PreparedVerifyingKey::deserialize_compressed(&bytes[..])
+    } else {
+        PreparedVerifyingKey::deserialize_compressed_unchecked(&bytes[..])
+    }
+}
+
+#[test]
+fn deserialized_prepared_key_matches_its_verifying_key() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+    let pvk = Polymath::process_vk(&vk).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+    let pvk2 = roundtrip(&pvk, true).unwrap();
+    assert!(Polymath::verify_with_processed_vk(&pvk2, &[a * b], &proof).unwrap());
+
+    // too few domain points would make the verifier index past the end,
+    // so the constants are checked even without validation
+    let mut bad = pvk.clone();
+    bad.omega_powers.pop();
+    for validate in [true, false] {
+        assert!(matches!(
+            roundtrip(&bad, validate),
+            Err(SerializationError::InvalidData)
+        ));
+    }
+
+    let mut bad = pvk.clone();
+    bad.n_inv += Fr::one();
+    assert!(roundtrip(&bad, false).is_err());
+
+    let mut bad = pvk.clone();
+    bad.omega_powers[1] = Fr::one();
+    assert!(roundtrip(&bad, false).is_err());
+
+    // prepared points that don't match the key
+    let mut bad = pvk.clone();
+    bad.z_g2 = pvk.x_g2.clone();
+    assert!(matches!(
+        roundtrip(&bad, true),
+        Err(SerializationError::InvalidData)
+    ));
+}
@@ -0,0 +1,124 @@
+use ark_bls12_381::{Bls12_381, Fq, Fr, G1Affine};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError, Proof};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+/// A point on the curve outside of the prime-order subgroup.
+fn non_subgroup_point<R: RngCore>(rng: &mut R) -> G1Affine {
+    loop {
+        if let Some(p) = G1Affine::get_point_from_x_unchecked(Fq::rand(rng), false) {
+            if !p.is_in_correct_subgroup_assuming_on_curve() {
+                return p;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_proof_validation() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+    let public_inputs = [a * b];
+
+    proof.validate().unwrap();
+    assert!(Polymath::verify_proof_strict(&vk, &public_inputs, &proof).unwrap());
+
+    let mut bytes = vec![];
+    proof.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(Proof::deserialize_compressed(&bytes[..]).unwrap(), proof);
+
+    let mut bad = proof.clone();
+    bad.a_g1 = G1Affine::zero();
+    bad.c_g1 = G1Affine::zero();
+    bad.d_g1 = G1Affine::zero();
+    assert!(matches!(
+        bad.validate(),
+        Err(PolymathError::InvalidProofElement {
+            element: "a_g1",
+            reason: "point at infinity"
+        })
+    ));
+    assert!(matches!(
+        Polymath::verify_proof_strict(&vk, &public_inputs, &bad),
+        Err(PolymathError::InvalidProofElement { .. })
+    ));
+    let mut bytes = vec![];
+    bad.serialize_compressed(&mut bytes).unwrap();
+    assert!(Proof::<Bls12_381>::deserialize_compressed(&bytes[..]).is_err());
+
+    let mut bad = proof.clone();
+    bad.c_g1 = non_subgroup_point(&mut rng);
+    assert!(matches!(
+        bad.validate(),
+        Err(PolymathError::InvalidProofElement {
+            element: "c_g1",
+            reason: "not in the prime-order subgroup"
+        })
+    ));
+    let mut bytes = vec![];
+    bad.serialize_uncompressed(&mut bytes).unwrap();
+    assert!(Proof::<Bls12_381>::deserialize_uncompressed(&bytes[..]).is_err());
+    assert!(Proof::<Bls12_381>::deserialize_uncompressed_unchecked(&bytes[..]).is_ok());
+}
+
+#[test]
+fn non_canonical_a_at_x1_is_rejected() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+    let circuit = DummyCircuit {
+        a: Some(Fr::rand(&mut rng)),
+        b: Some(Fr::rand(&mut rng)),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+
+    let mut bytes = vec![];
+    proof.serialize_compressed(&mut bytes).unwrap();
+
+    // `a_at_x1 + p` encodes the same field element if reduced modulo `p`
+    let offset = 2 * proof.a_g1.compressed_size();
+    let mut a_at_x1_plus_p = proof.a_at_x1.into_bigint();
+    a_at_x1_plus_p.add_with_carry(&Fr::MODULUS);
+    bytes[offset..offset + 32].copy_from_slice(&a_at_x1_plus_p.to_bytes_le());
+
+    assert!(Proof::<Bls12_381>::deserialize_compressed(&bytes[..]).is_err());
+    assert!(Proof::<Bls12_381>::deserialize_compressed_unchecked(&bytes[..]).is_err());
+}
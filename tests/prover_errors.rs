@@ -0,0 +1,92 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::{Field, One};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+/// `a·b = c` with public `c`, which is assigned `c_offset` more than `a·b`.
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    c_offset: F,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b + self.c_offset));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn unsatisfied_constraints_are_reported() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> {
+        a: None,
+        b: None,
+        c_offset: Fr::from(0),
+    };
+    let (pk, _) = Polymath::setup(circuit, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+        c_offset: Fr::one(),
+    };
+    assert!(matches!(
+        Polymath::prove(&pk, circuit, &mut rng),
+        Err(PolymathError::UnsatisfiedConstraints)
+    ));
+    assert!(matches!(
+        Polymath::prove_with_assignment(&pk, &[a * b], &[a, a], &mut rng),
+        Err(PolymathError::UnsatisfiedConstraints)
+    ));
+}
+
+#[test]
+fn malformed_proving_key_is_reported() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> {
+        a: None,
+        b: None,
+        c_offset: Fr::from(0),
+    };
+    let (pk, _) = Polymath::setup(circuit, &mut rng).unwrap();
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+
+    let mut bad = pk.clone();
+    bad.x_powers_y_gamma_z_g1.pop();
+    assert!(matches!(
+        Polymath::prove_with_assignment(&bad, &[a * b], &[a, b], &mut rng),
+        Err(PolymathError::InvalidKeyLength {
+            name: "x_powers_y_gamma_z_g1",
+            ..
+        })
+    ));
+
+    let mut bad = pk.clone();
+    bad.vk.n *= 2;
+    assert!(matches!(
+        Polymath::prepare_proving_key(&bad),
+        Err(PolymathError::InvalidKey(_))
+    ));
+}
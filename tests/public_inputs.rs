@@ -0,0 +1,93 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    /// Allocate `b` as a public input too.
+    b_public: bool,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = if self.b_public {
+            cs.new_input_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?
+        } else {
+            cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?
+        };
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn wrong_number_of_public_inputs_is_rejected() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> {
+        a: None,
+        b: None,
+        b_public: false,
+    };
+    let (pk, vk) = Polymath::setup(circuit, &mut rng).unwrap();
+    let pvk = Polymath::process_vk(&vk).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+        b_public: false,
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Polymath::verify(&vk, &[a * b], &proof).unwrap());
+
+    assert!(matches!(
+        Polymath::verify(&vk, &[], &proof),
+        Err(PolymathError::PublicInputLengthMismatch {
+            expected: 1,
+            got: 0
+        })
+    ));
+    assert!(matches!(
+        Polymath::verify_with_processed_vk(&pvk, &[a * b, a], &proof),
+        Err(PolymathError::PublicInputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    ));
+    assert!(matches!(
+        Polymath::verify_batch(&vk, &[(&[a * b, b], &proof)], &mut rng),
+        Err(PolymathError::BatchVerificationFailed { index: 0 })
+    ));
+
+    // the circuit allocates one more public input than the key was generated for
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+        b_public: true,
+    };
+    assert!(matches!(
+        Polymath::prove(&pk, circuit, &mut rng),
+        Err(PolymathError::PublicInputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    ));
+}
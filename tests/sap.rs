@@ -0,0 +1,165 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+use ark_ff::{Field, One, Zero};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError, ProvingKey, SAPMatrices};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+/// `d = (a·b + a)·b` with public `a` and `d`.
+struct Circuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for Circuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a_val = self.a;
+        let c_val = self.a.and_then(|a| self.b.map(|b| a * b));
+        let d_val = c_val.and_then(|c| self.a.and_then(|a| self.b.map(|b| (c + a) * b)));
+
+        let a = cs.new_input_variable(|| a_val.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_witness_variable(|| c_val.ok_or(SynthesisError::AssignmentMissing))?;
+        let d = cs.new_input_variable(|| d_val.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + c + a, lc!() + b, lc!() + d)
+    }
+}
+
+/// `Mz` computed from the non-zero elements of the columns of `M`.
+fn mul(pk: &ProvingKey<Bls12_381>, u: bool, z: &[Fr]) -> Vec<Fr> {
+    let m = if u {
+        pk.sap_matrices.u_csc()
+    } else {
+        pk.sap_matrices.w_csc()
+    };
+    let mut result = vec![Fr::zero(); m.num_rows()];
+    for (j, zj) in z.iter().enumerate() {
+        for (i, mij) in m.column(j) {
+            result[i] += mij * zj;
+        }
+    }
+    result
+}
+
+#[test]
+fn sparse_sap_matrices_encode_the_r1cs() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Polymath::setup(Circuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+    let sap = &pk.sap_matrices;
+    let (num_rows, num_columns) = sap.size();
+    assert_eq!(sap.u_csc().num_rows(), num_rows);
+    assert_eq!(sap.u_csc().num_columns(), num_columns);
+    assert_eq!(sap.w_csc().num_columns(), num_columns);
+
+    for j in 0..num_columns {
+        for (i, uij) in sap.u_csc().column(j) {
+            assert!(!uij.is_zero());
+            assert_eq!(sap.u(i, j), uij);
+        }
+    }
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let c = a * b;
+    let d = (c + a) * b;
+
+    // z = (x, x, w, y), x = (1, a, d), w = (b, c)
+    let x = [Fr::one(), a, d];
+    let w = [b, c];
+    let y = [
+        Fr::zero(),
+        (Fr::one() - a).square(),
+        (Fr::one() - d).square(),
+        (a - b).square(),
+        (c + a - b).square(),
+    ];
+    let z = [&x[..], &x, &w, &y].concat();
+    assert_eq!(z.len(), num_columns);
+
+    let uz = mul(&pk, true, &z);
+    let wz = mul(&pk, false, &z);
+    for i in 0..num_rows {
+        assert_eq!(uz[i].square(), wz[i], "SAP row {i} is not satisfied");
+    }
+
+    // a wrong witness breaks some row
+    let z = [&x[..], &x, &[b, c + Fr::one()], &y].concat();
+    let uz = mul(&pk, true, &z);
+    let wz = mul(&pk, false, &z);
+    assert!((0..num_rows).any(|i| uz[i].square() != wz[i]));
+}
+
+#[test]
+fn sparse_sap_matrices_are_rebuilt_on_deserialization() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Polymath::setup(Circuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let mut bytes = vec![];
+    pk.serialize_compressed(&mut bytes).unwrap();
+    let pk2 = ProvingKey::<Bls12_381>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(pk2.sap_matrices.u_csc(), pk.sap_matrices.u_csc());
+    assert_eq!(pk2.sap_matrices.w_csc(), pk.sap_matrices.w_csc());
+    assert_eq!(pk2.sap_matrices.u_rows(), pk.sap_matrices.u_rows());
+    assert_eq!(pk2.sap_matrices.w_rows(), pk.sap_matrices.w_rows());
+
+    // a column index past the last variable: the column of the first element of `A`
+    // comes after the three counts, the lengths of `A` and its first row, and the element
+    let mut bytes = vec![];
+    pk.sap_matrices.serialize_compressed(&mut bytes).unwrap();
+    let offset = 5 * 8 + Fr::one().compressed_size();
+    let column = pk.sap_matrices.a()[0][0].1 as u64;
+    assert_eq!(bytes[offset..offset + 8], column.to_le_bytes());
+    bytes[offset..offset + 8].copy_from_slice(&5u64.to_le_bytes());
+    assert!(SAPMatrices::<Fr>::deserialize_compressed(&bytes[..]).is_err());
+}
+
+#[test]
+fn oversized_counts_are_rejected() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Polymath::setup(Circuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+    let sap = &pk.sap_matrices;
+    let mut bytes = vec![];
+    sap.serialize_compressed(&mut bytes).unwrap();
+
+    // the counts of instance and witness variables are the first two words:
+    // overflowing sizes, and sizes past the largest evaluation domain of `Fr`
+    for (offset, count) in [
+        (0, u64::MAX),
+        (8, u64::MAX),
+        (8, u64::MAX / 2),
+        (0, 1 << 40),
+        (8, 1 << 40),
+    ] {
+        let mut bytes = bytes.clone();
+        bytes[offset..offset + 8].copy_from_slice(&count.to_le_bytes());
+        assert!(matches!(
+            SAPMatrices::<Fr>::deserialize_compressed(&bytes[..]),
+            Err(SerializationError::InvalidData)
+        ));
+    }
+
+    assert!(matches!(
+        SAPMatrices::new(
+            usize::MAX / 2,
+            usize::MAX / 2,
+            sap.a().clone(),
+            sap.b().clone(),
+            sap.c().clone(),
+        ),
+        Err(PolymathError::InvalidKey(_))
+    ));
+}
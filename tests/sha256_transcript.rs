@@ -0,0 +1,92 @@
+use ark_bls12_381::{Bls12_381, Fr, G1Affine};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::AffineRepr;
+use ark_ff::{Field, PrimeField};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand, Zero,
+};
+use charms_polymath::{sha256::Sha256Transcript, Transcript};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, Sha256Transcript<Fr>>;
+
+fn fr(hex: &str) -> Fr {
+    Fr::from_be_bytes_mod_order(&hex::decode(hex).unwrap())
+}
+
+/// The challenges for the Polymath messages, computed independently from the byte layout
+/// documented on [`Sha256Transcript`] with Python's `hashlib`.
+#[test]
+fn challenges_match_test_vectors() {
+    let mut t = Sha256Transcript::<Fr>::new(b"polymath");
+    t.append_message(b"protocol_version", 1u64.to_le_bytes());
+    t.append_scalars(b"public_inputs", &[Fr::from(1u64), Fr::from(15u64)])
+        .unwrap();
+    t.append_points(b"commitments", &[G1Affine::generator(), G1Affine::zero()])
+        .unwrap();
+    let x1 = t.challenge(b"x1");
+    assert_eq!(
+        x1,
+        fr("2c00db11efadd4ea3629cb4239ccdf12da54b9396e268cf284efffc2d6f42d78")
+    );
+
+    t.append_scalar(b"x1", &x1).unwrap();
+    t.append_scalars(b"values", &[-Fr::from(1u64), Fr::zero()])
+        .unwrap();
+    let x2 = t.challenge(b"x2");
+    assert_eq!(
+        x2,
+        fr("2936c10685506302dc5f001e501e5637552e05e30fda01367846af23fd1e24a7")
+    );
+}
+
+#[test]
+fn labels_and_messages_are_separated() {
+    let mut t1 = Sha256Transcript::<Fr>::new(b"polymath");
+    t1.append_message(b"ab", b"c");
+    let mut t2 = Sha256Transcript::<Fr>::new(b"polymath");
+    t2.append_message(b"a", b"bc");
+    assert_ne!(t1.challenge(b"x"), t2.challenge(b"x"));
+
+    let mut t1 = Sha256Transcript::<Fr>::new(b"polymath");
+    let mut t2 = Sha256Transcript::<Fr>::new(b"other");
+    assert_ne!(t1.challenge(b"x"), t2.challenge(b"x"));
+}
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn polymath_proof_verifies_with_sha256_transcript() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+
+    assert!(Polymath::verify(&vk, &[a * b], &proof).unwrap());
+    assert!(!Polymath::verify(&vk, &[a], &proof).unwrap());
+}
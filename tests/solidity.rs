@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+
+use ark_bn254::{Bn254, Fq, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{
+    keccak256::Keccak256Transcript, FiatShamirV0, FiatShamirV1, FiatShamirVersion,
+};
+use revm::{
+    db::InMemoryDB,
+    primitives::{Address, Bytes, ExecutionResult, Output, TxKind},
+    Evm,
+};
+use sha3::{Digest, Keccak256};
+use solang_parser::pt::{ContractPart, Expression, SourceUnitPart};
+
+type Polymath<V> = charms_polymath::Polymath<Bn254, Keccak256Transcript<Fr>, V>;
+
+/// `a·b = c` and `(a + b)·1 = s`, with `c` and `s` public.
+struct ProductAndSum<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for ProductAndSum<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+        let s = self.a.and_then(|a| self.b.map(|b| a + b));
+        let s = cs.new_input_variable(|| s.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        cs.enforce_constraint(lc!() + a + b, lc!() + Variable::One, lc!() + s)
+    }
+}
+
+/// Constants and function names of the generated contract.
+struct Contract {
+    constants: HashMap<String, Vec<u8>>,
+    functions: Vec<String>,
+}
+
+impl Contract {
+    fn parse(source: &str) -> Self {
+        let (unit, _) = solang_parser::parse(source, 0).expect("generated contract doesn't parse");
+
+        let mut constants = HashMap::new();
+        let mut functions = vec![];
+        for part in unit.0 {
+            let SourceUnitPart::ContractDefinition(contract) = part else {
+                continue;
+            };
+            assert_eq!(contract.name.unwrap().name, "PolymathVerifier");
+            for part in contract.parts {
+                match part {
+                    ContractPart::VariableDefinition(v) => {
+                        let value = match v.initializer.unwrap() {
+                            Expression::HexNumberLiteral(_, n, _) => {
+                                hex::decode(n.trim_start_matches("0x")).unwrap()
+                            },
+                            Expression::NumberLiteral(_, n, _, _) => {
+                                word(&n.parse::<u64>().unwrap().to_be_bytes())
+                            },
+                            Expression::HexLiteral(h) => hex::decode(
+                                h[0].hex.trim_start_matches("hex\"").trim_end_matches('"'),
+                            )
+                            .unwrap(),
+                            e => panic!("unexpected initializer {:?}", e),
+                        };
+                        constants.insert(v.name.unwrap().name, value);
+                    },
+                    ContractPart::FunctionDefinition(f) => functions.push(f.name.unwrap().name),
+                    _ => {},
+                }
+            }
+        }
+        Contract {
+            constants,
+            functions,
+        }
+    }
+
+    fn get(&self, name: &str) -> Vec<u8> {
+        self.constants[name].clone()
+    }
+
+    fn get_u64(&self, name: &str) -> u64 {
+        u64::from_be_bytes(self.get(name)[24..].try_into().unwrap())
+    }
+}
+
+/// Left-pad `bytes` to a 32-byte word.
+fn word(bytes: &[u8]) -> Vec<u8> {
+    let mut w = vec![0u8; 32 - bytes.len()];
+    w.extend(bytes);
+    w
+}
+
+fn be<F: PrimeField>(f: F) -> Vec<u8> {
+    f.into_bigint().to_bytes_be()
+}
+
+/// Append `bytes` prefixed with their length as a big-endian `u64`.
+fn append_bytes(preimage: &mut Vec<u8>, bytes: &[u8]) {
+    preimage.extend((bytes.len() as u64).to_be_bytes());
+    preimage.extend(bytes);
+}
+
+/// Call the precompile at `address` in a fresh EVM, `None` if the call fails.
+fn precompile(address: u8, input: Vec<u8>) -> Option<Vec<u8>> {
+    let mut to = [0u8; 20];
+    to[19] = address;
+    let mut evm = Evm::builder()
+        .with_db(InMemoryDB::default())
+        .modify_tx_env(|tx| {
+            tx.transact_to = TxKind::Call(Address::from(to));
+            tx.data = Bytes::from(input);
+            tx.gas_limit = 10_000_000;
+        })
+        .build();
+    match evm.transact().unwrap().result {
+        ExecutionResult::Success {
+            output: Output::Call(out),
+            ..
+        } => Some(out.to_vec()),
+        _ => None,
+    }
+}
+
+fn ec_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+    precompile(0x06, [p, q].concat()).expect("ecAdd failed")
+}
+
+fn ec_mul(p: &[u8], s: Fr) -> Vec<u8> {
+    precompile(0x07, [p, &be(s)].concat()).expect("ecMul failed")
+}
+
+/// Replays `verifyProof` of the generated contract on `calldata` step by step:
+/// the transcript bytes are built from the contract constants and the calldata words,
+/// and the group operations and the pairing check run on the EVM precompiles.
+fn verify_on_evm(contract: &Contract, calldata: &[u8]) -> bool {
+    let words: Vec<&[u8]> = calldata[4..].chunks(32).collect();
+    let proof = &words[..7];
+    let input = &words[9..];
+    assert_eq!(words[8], &word(&(input.len() as u64).to_be_bytes())[..]);
+    assert_eq!(input.len() as u64 + 1, contract.get_u64("M0"));
+
+    let scalar = |w: &[u8]| Fr::from_be_bytes_mod_order(w);
+    // wideChallenge: hi * 2^128 + lo
+    let wide_challenge = |h: &[u8]| {
+        let hi = Keccak256::new()
+            .chain_update(h)
+            .chain_update([0u8])
+            .finalize();
+        let lo = Keccak256::new()
+            .chain_update(h)
+            .chain_update([1u8])
+            .finalize();
+        scalar(&hi) * Fr::from(2u64).pow([128]) + scalar(&lo[..16])
+    };
+
+    // challengeX1
+    let m0 = contract.get_u64("M0") as usize;
+    let mut preimage = contract.get("TRANSCRIPT_PREFIX");
+    append_bytes(&mut preimage, b"public_inputs");
+    let mut inputs = word(&(m0 as u64).to_be_bytes());
+    inputs.extend(be(Fr::one()));
+    for x in input {
+        inputs.extend(*x);
+    }
+    append_bytes(&mut preimage, &inputs);
+    append_bytes(&mut preimage, b"commitments");
+    let mut commitments = word(&[2]);
+    for w in &proof[..4] {
+        commitments.extend(*w);
+    }
+    append_bytes(&mut preimage, &commitments);
+    append_bytes(&mut preimage, b"x1");
+    let state = Keccak256::digest(&preimage);
+    let x1 = wide_challenge(&state);
+
+    // evaluateC and evaluatePi
+    let omega = scalar(&contract.get("OMEGA"));
+    let y1 = x1.pow([contract.get_u64("SIGMA")]);
+    let y1_gamma = y1.inverse().unwrap().pow([5]);
+    let (mut num, mut den, mut w) = (Fr::zero(), Fr::one(), Fr::one());
+    for i in 0..2 * m0 {
+        let z = match i {
+            0 => Fr::from(2u64),
+            i if i < m0 => Fr::one() + scalar(input[i - 1]),
+            i if i == m0 => Fr::zero(),
+            i => Fr::one() - scalar(input[i - m0 - 1]),
+        };
+        let d = x1 - w;
+        num = num * d + z * w * den;
+        den *= d;
+        w *= omega;
+    }
+    let vanishing = x1.pow([contract.get_u64("N")]) - Fr::one();
+    let pi = num / den * vanishing * scalar(&contract.get("N_INV")) * y1_gamma;
+    let a_at_x1 = scalar(proof[4]);
+    let c_at_x1 = ((a_at_x1 + y1_gamma) * a_at_x1 - pi) * y1.pow([3]);
+
+    // challengeX2
+    let mut preimage = state.to_vec();
+    append_bytes(&mut preimage, b"x1");
+    append_bytes(&mut preimage, &be(x1));
+    append_bytes(&mut preimage, b"values");
+    let mut values = word(&[2]);
+    values.extend(proof[4]);
+    values.extend(be(c_at_x1));
+    append_bytes(&mut preimage, &values);
+    append_bytes(&mut preimage, b"x2");
+    let x2 = wide_challenge(&Keccak256::digest(&preimage));
+
+    // checkPairing
+    let g2 = |name: &str| {
+        ["X_IM", "X_RE", "Y_IM", "Y_RE"]
+            .iter()
+            .flat_map(|limb| contract.get(&format!("{}_{}", name, limb)))
+            .collect::<Vec<u8>>()
+    };
+    let one_g1 = [contract.get("ONE_G1_X"), contract.get("ONE_G1_Y")].concat();
+    let a_g1 = [proof[0], proof[1]].concat();
+    let c_g1 = [proof[2], proof[3]].concat();
+    let d_g1 = [proof[5], proof[6]].concat();
+    let minus_d_y = be(-Fq::from_be_bytes_mod_order(proof[6]));
+
+    let p = ec_mul(&one_g1, -(a_at_x1 + x2 * c_at_x1));
+    let p = ec_add(&p, &ec_mul(&c_g1, x2));
+    let p = ec_add(&p, &a_g1);
+
+    let pairing_input = [
+        p,
+        g2("Z_G2"),
+        proof[5].to_vec(),
+        minus_d_y,
+        g2("X_G2"),
+        ec_mul(&d_g1, x1),
+        g2("ONE_G2"),
+    ]
+    .concat();
+    precompile(0x08, pairing_input).expect("pairing failed") == word(&[1])
+}
+
+fn proofs_verify_on_evm<V: FiatShamirVersion>() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) =
+        Polymath::<V>::setup(ProductAndSum::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let contract = Contract::parse(&Polymath::<V>::solidity_verifier(&vk).unwrap());
+
+    for _ in 0..4 {
+        let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let public_inputs = [a * b, a + b];
+        let proof = Polymath::<V>::prove(
+            &pk,
+            ProductAndSum {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        assert!(Polymath::<V>::verify(&vk, &public_inputs, &proof).unwrap());
+
+        let calldata = Polymath::<V>::solidity_calldata(&proof, &public_inputs);
+        assert!(verify_on_evm(&contract, &calldata));
+
+        let calldata = Polymath::<V>::solidity_calldata(&proof, &[a * b, a - b]);
+        assert!(!verify_on_evm(&contract, &calldata));
+    }
+}
+
+#[test]
+fn proofs_verify_on_evm_v1() {
+    proofs_verify_on_evm::<FiatShamirV1>();
+}
+
+#[test]
+fn proofs_verify_on_evm_v0() {
+    proofs_verify_on_evm::<FiatShamirV0>();
+}
+
+#[test]
+fn generated_contract_binds_the_key() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (_, vk) =
+        Polymath::<FiatShamirV1>::setup(ProductAndSum::<Fr> { a: None, b: None }, &mut rng)
+            .unwrap();
+
+    let contract = Contract::parse(&Polymath::<FiatShamirV1>::solidity_verifier(&vk).unwrap());
+    for f in [
+        "verifyProof",
+        "challengeX1",
+        "challengeX2",
+        "evaluateC",
+        "evaluatePi",
+        "checkPairing",
+        "wideChallenge",
+    ] {
+        assert!(contract.functions.iter().any(|g| g == f), "missing {}", f);
+    }
+
+    let mut prefix = vec![];
+    append_bytes(&mut prefix, b"polymath");
+    append_bytes(&mut prefix, b"protocol_version");
+    append_bytes(&mut prefix, &1u64.to_le_bytes());
+    append_bytes(&mut prefix, b"vk_digest");
+    append_bytes(&mut prefix, &vk.digest().unwrap());
+    assert_eq!(contract.get("TRANSCRIPT_PREFIX"), prefix);
+    // the moduli the EVM precompiles work with
+    assert_eq!(
+        hex::encode(contract.get("R")),
+        "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"
+    );
+    assert_eq!(
+        hex::encode(contract.get("Q")),
+        "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47"
+    );
+    assert_eq!(contract.get_u64("N"), vk.n);
+    assert_eq!(contract.get_u64("SIGMA"), vk.sigma);
+    assert_eq!(contract.get("ONE_G1_X"), be(vk.e.one_g1.x().unwrap()));
+
+    let contract = Contract::parse(&Polymath::<FiatShamirV0>::solidity_verifier(&vk).unwrap());
+    assert_eq!(
+        contract.get("TRANSCRIPT_PREFIX"),
+        [&8u64.to_be_bytes()[..], b"polymath"].concat()
+    );
+}
+
+#[test]
+fn calldata_follows_the_abi() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, _) =
+        Polymath::<FiatShamirV1>::setup(ProductAndSum::<Fr> { a: None, b: None }, &mut rng)
+            .unwrap();
+    let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+    let public_inputs = [a * b, a + b];
+    let proof = Polymath::<FiatShamirV1>::prove(
+        &pk,
+        ProductAndSum {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let calldata = Polymath::<FiatShamirV1>::solidity_calldata(&proof, &public_inputs);
+    assert_eq!(
+        calldata[..4],
+        Keccak256::digest(b"verifyProof(uint256[7],uint256[])")[..4]
+    );
+    assert_eq!(calldata.len(), 4 + 32 * (9 + public_inputs.len()));
+
+    let words: Vec<&[u8]> = calldata[4..].chunks(32).collect();
+    let xy = |p: ark_bn254::G1Affine| {
+        let (x, y) = p.xy().unwrap();
+        [be(x), be(y)]
+    };
+    assert_eq!(words[..2], xy(proof.a_g1));
+    assert_eq!(words[2..4], xy(proof.c_g1));
+    assert_eq!(words[4], be(proof.a_at_x1));
+    assert_eq!(words[5..7], xy(proof.d_g1));
+    // the array starts after the 7 words of `proof` and its own offset
+    assert_eq!(words[7], word(&[0x01, 0x00]));
+    assert_eq!(words[8], word(&[2]));
+    assert_eq!(words[9], be(a * b));
+    assert_eq!(words[10], be(a + b));
+}
+
+/// The contract checked in for the bytecode test, for the key of [`bytecode_fixture`].
+const CONTRACT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/PolymathVerifier.sol"
+);
+
+/// Its creation bytecode in hex, compiled by `scripts/compile-verifier.sh`.
+const BYTECODE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/PolymathVerifier.bin"
+);
+
+/// Keys from a fixed seed, so the checked-in contract stays valid across runs.
+fn bytecode_fixture() -> (
+    charms_polymath::ProvingKey<Bn254>,
+    charms_polymath::VerifyingKey<Bn254>,
+) {
+    let mut rng = StdRng::seed_from_u64(0);
+    Polymath::<FiatShamirV1>::setup(ProductAndSum::<Fr> { a: None, b: None }, &mut rng).unwrap()
+}
+
+/// Fails when the generator output changes; `REGENERATE_VERIFIER=1` rewrites the checked-in
+/// contract, after which `scripts/compile-verifier.sh` recompiles it.
+#[test]
+fn checked_in_contract_is_up_to_date() {
+    let (_, vk) = bytecode_fixture();
+    let contract = Polymath::<FiatShamirV1>::solidity_verifier(&vk).unwrap();
+
+    if std::env::var_os("REGENERATE_VERIFIER").is_some() {
+        std::fs::write(CONTRACT_PATH, &contract).unwrap();
+    }
+    assert_eq!(
+        std::fs::read_to_string(CONTRACT_PATH).unwrap(),
+        contract,
+        "generated contract changed: run scripts/compile-verifier.sh"
+    );
+}
+
+/// Result of calling `verifyProof` of the contract at `address`, `None` if the call reverts.
+fn call_verify_proof(
+    evm: &mut Evm<'_, (), InMemoryDB>,
+    address: Address,
+    calldata: Vec<u8>,
+) -> Option<bool> {
+    evm.tx_mut().transact_to = TxKind::Call(address);
+    evm.tx_mut().data = Bytes::from(calldata);
+    match evm.transact().unwrap().result {
+        ExecutionResult::Success {
+            output: Output::Call(out),
+            ..
+        } => Some(out[..] == word(&[1])[..]),
+        _ => None,
+    }
+}
+
+#[test]
+#[ignore = "needs tests/data/PolymathVerifier.bin, built by scripts/compile-verifier.sh"]
+fn compiled_contract_verifies_proofs() {
+    let (pk, vk) = bytecode_fixture();
+    assert_eq!(
+        std::fs::read_to_string(CONTRACT_PATH).unwrap(),
+        Polymath::<FiatShamirV1>::solidity_verifier(&vk).unwrap(),
+        "checked-in contract is for another key"
+    );
+    let bytecode = hex::decode(std::fs::read_to_string(BYTECODE_PATH).unwrap().trim()).unwrap();
+
+    let mut evm = Evm::builder()
+        .with_db(InMemoryDB::default())
+        .modify_tx_env(|tx| {
+            tx.transact_to = TxKind::Create;
+            tx.data = Bytes::from(bytecode);
+            tx.gas_limit = 10_000_000;
+        })
+        .build();
+    let address = match evm.transact_commit().unwrap() {
+        ExecutionResult::Success {
+            output: Output::Create(_, Some(address)),
+            ..
+        } => address,
+        result => panic!("deployment failed: {:?}", result),
+    };
+
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    for _ in 0..4 {
+        let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let public_inputs = [a * b, a + b];
+        let proof = Polymath::<FiatShamirV1>::prove(
+            &pk,
+            ProductAndSum {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        let calldata = Polymath::<FiatShamirV1>::solidity_calldata(&proof, &public_inputs);
+        assert_eq!(call_verify_proof(&mut evm, address, calldata), Some(true));
+
+        let calldata = Polymath::<FiatShamirV1>::solidity_calldata(&proof, &[a * b, a - b]);
+        assert_eq!(call_verify_proof(&mut evm, address, calldata), Some(false));
+
+        let mut tampered = proof.clone();
+        tampered.a_at_x1 += Fr::one();
+        let calldata = Polymath::<FiatShamirV1>::solidity_calldata(&tampered, &public_inputs);
+        assert_eq!(call_verify_proof(&mut evm, address, calldata), Some(false));
+    }
+}
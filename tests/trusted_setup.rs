@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, PrimeGroup, VariableBaseMSM};
+use ark_ff::{Field, One, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, Proof, ProvingKey, Transcript};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+/// Proves knowledge of a square root of the public input.
+struct SquareCircuit<F: Field> {
+    root: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for SquareCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let root =
+            cs.new_witness_variable(|| self.root.ok_or(SynthesisError::AssignmentMissing))?;
+        let square = cs.new_input_variable(|| {
+            self.root
+                .map(|r| r.square())
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        cs.enforce_constraint(lc!() + root, lc!() + root, lc!() + square)
+    }
+}
+
+/// Run the setup with a seeded rng and sample `x` and `z` again from the same seed,
+/// the way the generator does.
+fn setup_with_trapdoors(seed: u64) -> (ProvingKey<Bls12_381>, Fr, Fr) {
+    let (pk, vk) = Polymath::setup(
+        SquareCircuit { root: None },
+        &mut StdRng::seed_from_u64(seed),
+    )
+    .unwrap();
+
+    let domain = Radix2EvaluationDomain::<Fr>::new(vk.n as usize).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let x = domain.sample_element_outside_domain(&mut rng);
+    let z = domain.sample_element_outside_domain(&mut rng);
+
+    let g2 = <Bls12_381 as Pairing>::G2::generator();
+    assert_eq!(vk.e.x_g2, (g2 * x).into_affine());
+    assert_eq!(vk.e.z_g2, (g2 * z).into_affine());
+
+    (pk, x, z)
+}
+
+/// A public input with no square root: no valid proof exists for it.
+fn non_square(rng: &mut impl RngCore) -> Fr {
+    loop {
+        let c = Fr::rand(rng);
+        if c.sqrt().is_none() {
+            return c;
+        }
+    }
+}
+
+/// Evaluate a Laurent polynomial, given as exponent-coefficient pairs, at `x`.
+fn evaluate(p: &BTreeMap<i64, Fr>, x: Fr) -> Fr {
+    p.iter()
+        .map(|(&e, &c)| {
+            if e < 0 {
+                c * x.inverse().unwrap().pow([e.unsigned_abs()])
+            } else {
+                c * x.pow([e as u64])
+            }
+        })
+        .sum()
+}
+
+/// Forge a proof for `public_inputs` from `pk` and `powers`, where `powers[k]` is `[x^(k-2𝜎)]₁`
+/// for `k ∈ [0, n)`, without knowing `x` or `z`.
+///
+/// With `A(X) = X^(-3𝜎)` and `C(X) = X^(-3𝜎) + X^(-5𝜎) - PI(X)·X^(-2𝜎)`, where `PI(X)`
+/// interpolates the public input terms, `C(x1)` is the value the verifier computes from `A(x1)`
+/// for any `x1`. The quotient `(A(X) + x2·C(X) - A(x1) - x2·C(x1))/(X - x1)` only has
+/// exponents in `[-5𝜎, -2𝜎 + n - 2]`, so `[d]₁` is a combination of `x_powers_y_gamma_z_g1`.
+fn forge(
+    pk: &ProvingKey<Bls12_381>,
+    public_inputs: &[Fr],
+    powers: &[G1Affine],
+) -> Proof<Bls12_381> {
+    let vk = &pk.vk;
+    let n = vk.n as usize;
+    let m0 = vk.m0 as usize;
+    let sigma = vk.sigma as i64;
+    assert_eq!(powers.len(), n);
+
+    // `z̃ᵢ` at the rows `[0, 2·m₀)` of the domain, zero elsewhere
+    let public_inputs = [&[Fr::one()], public_inputs].concat();
+    let mut pi_evals = vec![Fr::zero(); n];
+    for i in 0..m0 {
+        pi_evals[i] = Fr::one() + public_inputs[i];
+        pi_evals[m0 + i] = Fr::one() - public_inputs[i];
+    }
+    let pi = Radix2EvaluationDomain::<Fr>::new(n)
+        .unwrap()
+        .ifft(&pi_evals);
+
+    let a_poly = BTreeMap::from([(-3 * sigma, Fr::one())]);
+    let mut c_poly = BTreeMap::from([(-3 * sigma, Fr::one()), (-5 * sigma, Fr::one())]);
+    for (k, pi_k) in pi.iter().enumerate() {
+        *c_poly.entry(k as i64 - 2 * sigma).or_default() -= pi_k;
+    }
+
+    let a_g1 = pk.x_powers_y_alpha_g1[0];
+    let c_g1 = (pk.x_powers_y_alpha_g1[0] + pk.x_powers_y_gamma_g1[0]
+        - G1Projective::msm(powers, &pi).unwrap())
+    .into_affine();
+
+    // the verifier's transcript
+    let mut t = MerlinFieldTranscript::<Fr>::new(b"polymath");
+    t.append_message(b"protocol_version", 1u64.to_le_bytes());
+    t.append_message(b"vk_digest", vk.digest().unwrap());
+    t.append_scalars(b"public_inputs", &public_inputs).unwrap();
+    t.append_points(b"commitments", &[a_g1, c_g1]).unwrap();
+    let x1 = t.challenge(b"x1");
+
+    let a_at_x1 = evaluate(&a_poly, x1);
+    let c_at_x1 = evaluate(&c_poly, x1);
+    t.append_scalar(b"x1", &x1).unwrap();
+    t.append_scalars(b"values", &[a_at_x1, c_at_x1]).unwrap();
+    let x2 = t.challenge(b"x2");
+
+    // (X^(-m) - x1^(-m))/(X - x1) = -∑ X^(i-m)·x1^(-1-i), i ∈ [0, m)
+    let x1_inv = x1.inverse().unwrap();
+    let mut quotient = BTreeMap::<i64, Fr>::new();
+    for (e, coeff) in c_poly.iter().map(|(&e, &c)| (e, x2 * c)).chain(a_poly) {
+        assert!(e < 0);
+        let mut x1_power = x1_inv;
+        for i in 0..-e {
+            *quotient.entry(i + e).or_default() -= coeff * x1_power;
+            x1_power *= x1_inv;
+        }
+    }
+
+    let (bases, scalars): (Vec<G1Affine>, Vec<Fr>) = quotient
+        .into_iter()
+        .map(|(e, q)| (pk.x_powers_y_gamma_z_g1[(e + 5 * sigma) as usize], q))
+        .unzip();
+    let d_g1 = G1Projective::msm(&bases, &scalars).unwrap().into_affine();
+
+    Proof {
+        a_g1,
+        c_g1,
+        a_at_x1,
+        d_g1,
+    }
+}
+
+#[test]
+fn x_is_enough_to_forge() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, x, _) = setup_with_trapdoors(rng.next_u64());
+    let sigma = pk.vk.sigma;
+
+    let x_inv = x.inverse().unwrap();
+    let powers: Vec<G1Affine> = (0..pk.vk.n)
+        .map(|k| {
+            let power = if k < 2 * sigma {
+                x_inv.pow([2 * sigma - k])
+            } else {
+                x.pow([k - 2 * sigma])
+            };
+            (G1Affine::generator() * power).into_affine()
+        })
+        .collect();
+
+    let public_input = non_square(&mut rng);
+    let proof = forge(&pk, &[public_input], &powers);
+    assert!(Polymath::verify(&pk.vk, &[public_input], &proof).unwrap());
+}
+
+#[test]
+fn z_is_enough_to_forge() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, _, z) = setup_with_trapdoors(rng.next_u64());
+    let sigma = pk.vk.sigma as usize;
+
+    // x_powers_y_gamma_z_g1[j] = [x^(j-5𝜎)·z]₁
+    let z_inv = z.inverse().unwrap();
+    let powers: Vec<G1Affine> = pk.x_powers_y_gamma_z_g1[3 * sigma..][..pk.vk.n as usize]
+        .iter()
+        .map(|p| (*p * z_inv).into_affine())
+        .collect();
+
+    let public_input = non_square(&mut rng);
+    let proof = forge(&pk, &[public_input], &powers);
+    assert!(Polymath::verify(&pk.vk, &[public_input], &proof).unwrap());
+}
+
+#[test]
+fn powers_of_tau_are_enough_to_forge() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, x, _) = setup_with_trapdoors(rng.next_u64());
+    let n = pk.vk.n as usize;
+    let sigma = pk.vk.sigma as usize;
+
+    // a transcript for `τ = x` with `[τⁱ]₁ = [x^(i-5𝜎)]₁`, large enough for the key
+    let mut power = G1Affine::generator() * x.inverse().unwrap().pow([5 * sigma as u64]);
+    let ptau: Vec<G1Affine> = (0..2 * n - 1 + 8 * sigma)
+        .map(|_| {
+            let p = power.into_affine();
+            power *= x;
+            p
+        })
+        .collect();
+    assert_eq!(ptau[5 * sigma], pk.vk.e.one_g1);
+    assert_eq!(ptau[5 * sigma..][..n + 1], pk.x_powers_g1);
+    assert_eq!(ptau[2 * sigma..][..3], pk.x_powers_y_alpha_g1);
+    assert_eq!(ptau[..2], pk.x_powers_y_gamma_g1);
+
+    let public_input = non_square(&mut rng);
+    let proof = forge(&pk, &[public_input], &ptau[3 * sigma..][..n]);
+    assert!(Polymath::verify(&pk.vk, &[public_input], &proof).unwrap());
+}
@@ -0,0 +1,114 @@
+use ark_bls12_381::{Bls12_381, Fq, Fr};
+use ark_crypto_primitives::snark::{
+    constraints::{EmulatedFieldInputVar, SNARKGadget},
+    CircuitSpecificSetupSNARK, SNARK,
+};
+use ark_ff::Field;
+use ark_r1cs_std::{
+    alloc::AllocVar, boolean::Boolean, eq::EqGadget, pairing::bls12, uint8::UInt8, R1CSVar,
+};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{
+    constraints::{PolymathVerifierGadget, ProofVar, VerifyingKeyVar},
+    poseidon::{constraints::PoseidonTranscriptVar, PoseidonTranscript},
+};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, PoseidonTranscript<Fr, Fq>>;
+type Gadget = PolymathVerifierGadget<
+    Bls12_381,
+    bls12::PairingVar<ark_bls12_381::Config>,
+    PoseidonTranscriptVar<Fr, Fq>,
+>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+    }
+}
+
+#[test]
+fn polymath_proof_verifies_in_circuit() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Polymath::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Polymath::verify(&vk, &[a * b], &proof).unwrap());
+    let ctx_proof = Polymath::prove_with_context(
+        &pk,
+        DummyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        b"tx",
+        &mut rng,
+    )
+    .unwrap();
+
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    let vk_var = VerifyingKeyVar::new_constant(cs.clone(), &vk).unwrap();
+    let proof_var = ProofVar::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+    let input_var = EmulatedFieldInputVar::new_input(cs.clone(), || Ok(vec![a * b])).unwrap();
+    let wrong_input_var = EmulatedFieldInputVar::new_input(cs.clone(), || Ok(vec![a])).unwrap();
+
+    let pvk_var = vk_var.prepare().unwrap();
+    Gadget::verify_with_processed_vk(&pvk_var, &input_var, &proof_var)
+        .unwrap()
+        .enforce_equal(&Boolean::TRUE)
+        .unwrap();
+
+    let wrong_input = Gadget::verify_with_processed_vk(&pvk_var, &wrong_input_var, &proof_var);
+    assert!(!wrong_input.unwrap().value().unwrap());
+
+    let ctx_proof_var = ProofVar::new_witness(cs.clone(), || Ok(&ctx_proof)).unwrap();
+    let ctx = UInt8::new_witness_vec(cs.clone(), b"tx").unwrap();
+    Gadget::verify_with_context(&pvk_var, &input_var, &ctx_proof_var, &ctx)
+        .unwrap()
+        .enforce_equal(&Boolean::TRUE)
+        .unwrap();
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn verifying_key_is_not_allocated_as_witness() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+    let (_, vk) = Polymath::setup(DummyCircuit::<Fr> { a: None, b: None }, &mut rng).unwrap();
+
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    assert!(matches!(
+        VerifyingKeyVar::<Bls12_381, bls12::PairingVar<ark_bls12_381::Config>>::new_witness(
+            cs.clone(),
+            || Ok(&vk)
+        ),
+        Err(SynthesisError::MalformedVerifyingKey)
+    ));
+    VerifyingKeyVar::<Bls12_381, bls12::PairingVar<ark_bls12_381::Config>>::new_input(cs, || {
+        Ok(&vk)
+    })
+    .unwrap();
+}
@@ -0,0 +1,62 @@
+// Verification against fixed proofs, without the prover: these tests also run in verifier-only
+// builds (`--no-default-features`).
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_serialize::CanonicalDeserialize;
+use ark_std::test_rng;
+use charms_polymath::{merlin::MerlinFieldTranscript, Proof, VerifyingKey};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+/// Verifying key for `a·b = c` with `c` public, compressed.
+const VK: &str = concat!(
+    "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+    "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+    "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+    "ab8b28bfbb362a4effde28779dd580e2f0f413be25c5eae1c4b791c823053a39576e75936e6bc478cf567ecec0d647c1",
+    "16cf205ef41016494f9d9e5cd42f8d43c14048450a175084e2d89de8371eab8356613efc2f59b541023e7e382bb01e4b",
+    "8a151d3998bc783629fa35c1f90b27f506be14a5f3f10289612d0fc87407a1a776249b2f6fa8a97fd94d37cdec154f6e",
+    "192fee739a8e8685289bca273713bc0d8defd5734bc36b84e6cd3ba1fccf7eb115c26510719859257e02381b93888ac3",
+    "080000000000000002000000000000000b000000000000007a3f749733fd287200b71387c2218bb3e27cd770cd25068c",
+    "e766fa03f6665734",
+);
+
+/// Proof for `3·5 = 15`, compressed.
+const PROOF: &str = concat!(
+    "b01c77a3b6beb65653291ad5c26881b765c88cd493259e983e2773d47d09a9b1b9f71cea5d1a7d260c583452314cfb3b",
+    "9094c41324a322b3d4bede7b5482868f9935d066930a3113df4fbffc949359ad2eef1b970301f25c75c17b72de911e85",
+    "f55578dacb51be8d7626047b17f465dbaf297c274e1ec1e8fda6e3477179a365a2f3861e1c2abb5a6fc77aa0f9ddbc7e",
+    "b34d3eaa9b3b4a0844610d2c170cf2956ae8270362fd2d73ef6952445be3ff6b",
+);
+
+/// Proof for `3·5 = 15` bound to the context `b"ctx"`, compressed.
+const PROOF_WITH_CONTEXT: &str = concat!(
+    "b50d0b214797143ac624775b7d1d464be880812941c8979289ce86e5afb8e06dd28865cef0bb8041c78637a7ad9917a1",
+    "aaf1045ea39b818cbce49b713dce704cb0ba2d73f5f0d4af4f4fad35fdf0d11e38ef3a52af1b8008ccf6bc8dbddce7fc",
+    "821b30f09ea30ae9abef9d1f56403aa4acb2fb9002b14c8c2d2950c3689f54039751dc14f55f30df13ed109bb182ccc7",
+    "7675b02f7e9c6551fb3d4e73af7b86671e64e00eafdcebeda0be8b397e4512fd",
+);
+
+fn decode<T: CanonicalDeserialize>(s: &str) -> T {
+    T::deserialize_compressed(&hex::decode(s).unwrap()[..]).unwrap()
+}
+
+#[test]
+fn fixed_proofs_verify() {
+    let vk: VerifyingKey<Bls12_381> = decode(VK);
+    let proof: Proof<Bls12_381> = decode(PROOF);
+    let proof_with_context: Proof<Bls12_381> = decode(PROOF_WITH_CONTEXT);
+
+    let c = Fr::from(15u64);
+    assert!(Polymath::verify_with_vk(&vk, &[c], &proof).unwrap());
+    assert!(!Polymath::verify_with_vk(&vk, &[c + Fr::from(1u64)], &proof).unwrap());
+
+    assert!(Polymath::verify_proof_strict(&vk, &[c], &proof).unwrap());
+    assert!(!Polymath::verify_proof_strict(&vk, &[c + Fr::from(1u64)], &proof).unwrap());
+
+    assert!(Polymath::verify_with_context(&vk, &[c], &proof_with_context, b"ctx").unwrap());
+    assert!(!Polymath::verify_with_context(&vk, &[c], &proof_with_context, b"xtc").unwrap());
+    assert!(!Polymath::verify_with_context(&vk, &[c], &proof, b"ctx").unwrap());
+
+    Polymath::verify_batch(&vk, &[(&[c], &proof), (&[c], &proof)], &mut test_rng()).unwrap();
+}
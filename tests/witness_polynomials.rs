@@ -0,0 +1,126 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ff::Field;
+use ark_poly::univariate::SparsePolynomial;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand,
+};
+use charms_polymath::{merlin::MerlinFieldTranscript, PolymathError};
+
+type Polymath = charms_polymath::Polymath<Bls12_381, MerlinFieldTranscript<Fr>>;
+
+struct DummyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    num_constraints: usize,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let c = self.a.and_then(|a| self.b.map(|b| a * b));
+        let c = cs.new_input_variable(|| c.ok_or(SynthesisError::AssignmentMissing))?;
+
+        for _ in 0..self.num_constraints {
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn proofs_from_witness_polynomials_verify_and_differ() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = DummyCircuit::<Fr> {
+        a: None,
+        b: None,
+        num_constraints: 5,
+    };
+    let (pk, vk) = Polymath::setup(circuit, &mut rng).unwrap();
+    let ppk = Polymath::prepare_proving_key(&pk).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let circuit = DummyCircuit {
+        a: Some(a),
+        b: Some(b),
+        num_constraints: 5,
+    };
+    let wp = Polymath::compute_witness_polynomials(&ppk, circuit).unwrap();
+    assert_eq!(
+        wp.u_poly,
+        Polymath::compute_witness_polynomials_with_assignment(&ppk, &[a * b], &[a, b])
+            .unwrap()
+            .u_poly
+    );
+
+    let proof1 = Polymath::prove_with_witness_polynomials(&ppk, &wp, None, &mut rng).unwrap();
+    let proof2 = Polymath::prove_with_witness_polynomials(&ppk, &wp, None, &mut rng).unwrap();
+    assert!(Polymath::verify(&vk, &[a * b], &proof1).unwrap());
+    assert!(Polymath::verify(&vk, &[a * b], &proof2).unwrap());
+    assert_ne!(proof1.a_g1, proof2.a_g1);
+    assert_ne!(proof1.c_g1, proof2.c_g1);
+    assert_ne!(proof1.d_g1, proof2.d_g1);
+
+    let proof = Polymath::prove_with_witness_polynomials(&ppk, &wp, Some(b"tx"), &mut rng).unwrap();
+    assert!(Polymath::verify_with_context(&vk, &[a * b], &proof, b"tx").unwrap());
+    assert!(!Polymath::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+#[test]
+fn witness_polynomials_for_another_key_are_rejected() {
+    let mut rng = StdRng::seed_from_u64(test_rng().next_u64());
+
+    let setup = |num_constraints, rng: &mut StdRng| {
+        let circuit = DummyCircuit::<Fr> {
+            a: None,
+            b: None,
+            num_constraints,
+        };
+        Polymath::setup(circuit, rng).unwrap().0
+    };
+    let large_pk = setup(40, &mut rng);
+    let small_pk = setup(1, &mut rng);
+    let large_ppk = Polymath::prepare_proving_key(&large_pk).unwrap();
+    let small_ppk = Polymath::prepare_proving_key(&small_pk).unwrap();
+
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let wp = Polymath::compute_witness_polynomials_with_assignment(&large_ppk, &[a * b], &[a, b])
+        .unwrap();
+
+    assert!(matches!(
+        Polymath::prove_with_witness_polynomials(&small_ppk, &wp, None, &mut rng),
+        Err(PolymathError::InvalidKeyLength { name: "u_poly", .. })
+    ));
+
+    // the fields are public: lengths that don't fit the key are errors, not truncated commitments
+    let mut bad = wp.clone();
+    bad.instance_assignment.push(a);
+    assert!(matches!(
+        Polymath::prove_with_witness_polynomials(&large_ppk, &bad, None, &mut rng),
+        Err(PolymathError::PublicInputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    ));
+
+    let mut bad = wp.clone();
+    let len = large_pk.x_powers_y_gamma_z_g1.len();
+    bad.c_without_r_a_x_by_y_gamma_poly = SparsePolynomial::from_coefficients_vec(vec![(len, a)]);
+    assert!(matches!(
+        Polymath::prove_with_witness_polynomials(&large_ppk, &bad, None, &mut rng),
+        Err(PolymathError::InvalidKeyLength {
+            name: "c_without_r_a_x_by_y_gamma_poly",
+            ..
+        })
+    ));
+}